@@ -0,0 +1,66 @@
+#[macro_use] extern crate criterion;
+extern crate suffix_tree;
+
+use criterion::{BatchSize, Criterion};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use suffix_tree::SuffixTreeBuilder;
+use suffix_tree::alphabet::Alphabet;
+use suffix_tree::util::strip_trailing;
+
+
+fn setup() -> Vec<Vec<u8>> {
+    let file = File::open("benches/resources/codon_sequences.txt").unwrap();
+    let mut reader = BufReader::new(file);
+
+    let mut sequences: Vec<Vec<u8>> = Vec::new();
+    loop {
+        let mut sequence = Vec::new();
+        if reader.read_until(b'\n', &mut sequence).unwrap() == 0 {
+            break;
+        }
+
+        let length = strip_trailing(&sequence, b'\n').len();
+        sequence.truncate(length);
+        sequences.push(sequence);
+    }
+
+    sequences
+}
+
+fn construct(sequences: Vec<Vec<u8>>, alphabet: Alphabet) {
+    let mut builder = SuffixTreeBuilder::new(Some(alphabet));
+    for sequence in &sequences {
+        builder.add_sequence(sequence);
+    }
+    let _ = builder.build();
+}
+
+fn dna_alphabet() -> Alphabet<'static> {
+    Alphabet::from_fn(
+        4,
+        |symbol| match symbol {
+            b'A' => Some(0), b'T' => Some(1), b'G' => Some(2), b'C' => Some(3),
+            _ => None,
+        },
+        |rank| b"ATGC"[rank as usize],
+    )
+}
+
+fn benchmark(c: &mut Criterion) {
+    c.bench_function("online construction codon sequences", |b| {
+        b.iter_batched(setup, |sequences| construct(sequences, Alphabet::new(b"ATGC")), BatchSize::LargeInput);
+    });
+
+    c.bench_function("online construction codon sequences rank fn", |b| {
+        b.iter_batched(setup, |sequences| construct(sequences, dna_alphabet()), BatchSize::LargeInput);
+    });
+}
+
+criterion_group!{
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = benchmark
+}
+
+criterion_main!(benches);
@@ -0,0 +1,70 @@
+#[macro_use] extern crate criterion;
+extern crate suffix_tree;
+
+use criterion::Criterion;
+use suffix_tree::SuffixTree;
+use suffix_tree::alphabet::Alphabet;
+
+
+const SEQUENCE_LENGTH: usize = 1_000_000;
+const QUERY_COUNT: usize = 1_000;
+
+// A xorshift generator, so that the sequence and queries are the same on
+// every run.
+fn random_numbers(mut state: u64) -> impl Iterator<Item = u64> {
+    std::iter::repeat_with(move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    })
+}
+
+fn setup() -> (Vec<u8>, Vec<Vec<u8>>) {
+    let sequence: Vec<u8> = random_numbers(1).take(SEQUENCE_LENGTH)
+        .map(|number| b"ACGT"[(number % 4) as usize])
+        .collect();
+
+    // Half of the queries are taken from the sequence, the other half are
+    // random and mostly diverge after a few edges.
+    let mut numbers = random_numbers(2);
+    let queries = (0..QUERY_COUNT).map(|i| {
+        let length = 8 + (numbers.next().unwrap() % 24) as usize;
+        if i % 2 == 0 {
+            let start = (numbers.next().unwrap() as usize) % (SEQUENCE_LENGTH - length);
+            sequence[start..start + length].to_vec()
+        } else {
+            numbers.by_ref().take(length).map(|number| b"ACGT"[(number % 4) as usize]).collect()
+        }
+    }).collect();
+
+    (sequence, queries)
+}
+
+fn benchmark(c: &mut Criterion) {
+    let (sequence, queries) = setup();
+    let tree = SuffixTree::from_sequence(&sequence, Some(Alphabet::new(b"ACGT")));
+
+    c.bench_function("find throughput", |b| {
+        b.iter(|| queries.iter().map(|query| tree.find(query).count()).sum::<usize>());
+    });
+
+    c.bench_function("contains throughput", |b| {
+        b.iter(|| queries.iter().filter(|query| tree.contains(query)).count());
+    });
+
+    // Membership checks of short patterns end after a few edges, so the cost
+    // of walking the path dominates.
+    let short_queries: Vec<&[u8]> = queries.iter().map(|query| &query[..4]).collect();
+    c.bench_function("contains short patterns", |b| {
+        b.iter(|| short_queries.iter().filter(|query| tree.contains(query)).count());
+    });
+}
+
+criterion_group!{
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = benchmark
+}
+
+criterion_main!(benches);
@@ -1,11 +1,12 @@
 #[macro_use] extern crate criterion;
 extern crate suffix_tree;
 
-use criterion::Criterion;
+use criterion::{BatchSize, Criterion};
 use std::fs::File;
 use std::io::{BufRead, BufReader};
 use suffix_tree::longest_common_subsequence;
 use suffix_tree::alphabet::Alphabet;
+use suffix_tree::util::strip_trailing;
 
 
 fn setup() -> Vec<Vec<u8>> {
@@ -15,7 +16,7 @@ fn setup() -> Vec<Vec<u8>> {
     let mut sequences: Vec<Vec<u8>> = Vec::new();
     loop {
         let mut sequence = Vec::new();
-        if reader.read_until('\n' as u8, &mut sequence).unwrap() == 0 {
+        if reader.read_until(b'\n', &mut sequence).unwrap() == 0 {
             break;
         }
 
@@ -26,17 +27,14 @@ fn setup() -> Vec<Vec<u8>> {
 }
 
 fn compute(strings: Vec<Vec<u8>>) {
-    let sequences: Vec<&[u8]> = strings.iter().map(|v| {
-        let slice = v.as_slice();
-        &slice[..slice.len() - 1]
-    }).collect();
+    let sequences: Vec<&[u8]> = strings.iter().map(|v| strip_trailing(v, b'\n')).collect();
     let alphabet = Alphabet::new(b"ATGC");
     let _ = longest_common_subsequence(&sequences, Some(alphabet)).unwrap();
 }
 
 fn benchmark(c: &mut Criterion) {
     c.bench_function("lcs codon sequence", move |b| {
-        b.iter_with_large_setup(|| setup(), |strings| compute(strings));
+        b.iter_batched(setup, compute, BatchSize::LargeInput);
     });
 }
 
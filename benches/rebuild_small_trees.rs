@@ -0,0 +1,36 @@
+#[macro_use] extern crate criterion;
+extern crate suffix_tree;
+
+use criterion::Criterion;
+use suffix_tree::{SuffixTree, SuffixTreeBuilder};
+
+const SEQUENCES: &[&[u8]] = &[b"testing", b"festung", b"estland", b"resting", b"fasting"];
+
+fn fresh_builders() {
+    for &a in SEQUENCES {
+        for &b in SEQUENCES {
+            let _ = SuffixTree::from_sequences(&[a, b], None);
+        }
+    }
+}
+
+fn reused_builder() {
+    let mut builder = SuffixTreeBuilder::new(None);
+    for &a in SEQUENCES {
+        for &b in SEQUENCES {
+            builder.add_sequence(a);
+            builder.add_sequence(b);
+
+            builder = builder.build().resume();
+            builder.reset();
+        }
+    }
+}
+
+fn benchmark(c: &mut Criterion) {
+    c.bench_function("rebuild small trees fresh", |b| b.iter(fresh_builders));
+    c.bench_function("rebuild small trees reused", |b| b.iter(reused_builder));
+}
+
+criterion_group!(benches, benchmark);
+criterion_main!(benches);
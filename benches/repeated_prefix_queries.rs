@@ -0,0 +1,61 @@
+#[macro_use] extern crate criterion;
+extern crate suffix_tree;
+
+use criterion::Criterion;
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use suffix_tree::{QueryCache, SuffixTreeBuilder};
+use suffix_tree::alphabet::Alphabet;
+use suffix_tree::util::strip_trailing;
+
+
+fn setup() -> Vec<Vec<u8>> {
+    let file = File::open("benches/resources/codon_sequences.txt").unwrap();
+    let mut reader = BufReader::new(file);
+
+    let mut sequences: Vec<Vec<u8>> = Vec::new();
+    loop {
+        let mut sequence = Vec::new();
+        if reader.read_until(b'\n', &mut sequence).unwrap() == 0 {
+            break;
+        }
+
+        let length = strip_trailing(&sequence, b'\n').len();
+        sequence.truncate(length);
+        sequences.push(sequence);
+    }
+
+    sequences
+}
+
+const PATTERNS: [&[u8]; 4] = [b"ATG", b"ATGC", b"ATGCA", b"ATGCAT"];
+
+fn benchmark(c: &mut Criterion) {
+    let sequences = setup();
+    let mut builder = SuffixTreeBuilder::new(Some(Alphabet::new(b"ATGC")));
+    for sequence in &sequences {
+        builder.add_sequence(sequence);
+    }
+    let tree = builder.build();
+
+    c.bench_function("repeated prefix queries", |b| {
+        b.iter(|| {
+            PATTERNS.iter().map(|pattern| tree.find(pattern).count()).sum::<usize>()
+        });
+    });
+
+    let cache = QueryCache::new(&tree, 16);
+    c.bench_function("repeated prefix queries cached", |b| {
+        b.iter(|| {
+            PATTERNS.iter().map(|pattern| cache.find(pattern).count()).sum::<usize>()
+        });
+    });
+}
+
+criterion_group!{
+    name = benches;
+    config = Criterion::default().sample_size(20);
+    targets = benchmark
+}
+
+criterion_main!(benches);
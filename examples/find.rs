@@ -1,4 +1,4 @@
-#[macro_use] extern crate structopt;
+extern crate structopt;
 extern crate suffix_tree;
 
 use structopt::StructOpt;
@@ -18,10 +18,10 @@ struct Options {
 fn main() {
     let options = Options::from_args();
 
-    let alphabet = options.alphabet.as_ref().map(|ref s| Alphabet::new(s.as_bytes()));
+    let alphabet = options.alphabet.as_ref().map(|s| Alphabet::new(s.as_bytes()));
 
-    let tree = SuffixTree::from_sequence(&options.string.as_bytes(), alphabet);
-    for (_, start, end) in tree.find(&options.pattern.as_bytes()) {
+    let tree = SuffixTree::from_sequence(options.string.as_bytes(), alphabet);
+    for (_, start, end) in tree.find(options.pattern.as_bytes()) {
         println!("{} {}", start, end);
     }
 }
@@ -0,0 +1,56 @@
+extern crate structopt;
+extern crate suffix_tree;
+
+use std::fs::File;
+use std::io;
+use std::io::{BufRead, BufReader};
+use std::path::PathBuf;
+use structopt::StructOpt;
+use suffix_tree::SuffixTree;
+use suffix_tree::alphabet::Alphabet;
+use suffix_tree::util::strip_trailing;
+
+#[derive(StructOpt)]
+struct Options {
+    #[structopt(short = "a", long = "alphabet")]
+    alphabet: Option<String>,
+    #[structopt(name = "CORPUS", parse(from_os_str))]
+    corpus_path: PathBuf,
+}
+
+fn read_line<R: BufRead>(reader: &mut R) -> io::Result<Option<Vec<u8>>> {
+    let mut line = Vec::new();
+    if reader.read_until(b'\n', &mut line)? == 0 {
+        return Ok(None);
+    }
+
+    let length = strip_trailing(&line, b'\n').len();
+    line.truncate(length);
+
+    Ok(Some(line))
+}
+
+fn main() -> io::Result<()> {
+    let options = Options::from_args();
+
+    let mut reader = BufReader::new(File::open(options.corpus_path)?);
+    let mut owned_sequences: Vec<Vec<u8>> = Vec::new();
+    while let Some(sequence) = read_line(&mut reader)? {
+        owned_sequences.push(sequence);
+    }
+
+    let sequences: Vec<&[u8]> = owned_sequences.iter().map(|v| v.as_slice()).collect();
+
+    let alphabet = options.alphabet.as_ref().map(|s| Alphabet::new(s.as_bytes()));
+    let tree = SuffixTree::from_sequences(&sequences, alphabet);
+
+    let stdin = io::stdin();
+    let mut patterns = stdin.lock();
+    while let Some(pattern) = read_line(&mut patterns)? {
+        for (seq_id, start, end) in tree.find(&pattern) {
+            println!("{}:{}-{}", seq_id, start, end);
+        }
+    }
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-#[macro_use] extern crate structopt;
+extern crate structopt;
 extern crate suffix_tree;
 
 use std::fs::File;
@@ -7,8 +7,9 @@ use std::io::{BufRead, BufReader};
 use std::path::PathBuf;
 use std::str;
 use structopt::StructOpt;
+use suffix_tree::SuffixTree;
 use suffix_tree::alphabet::Alphabet;
-use suffix_tree::longest_common_subsequence;
+use suffix_tree::util::strip_trailing;
 
 #[derive(StructOpt)]
 struct Options {
@@ -31,10 +32,12 @@ fn main() -> io::Result<()> {
         let mut sequences: Vec<Vec<u8>> = Vec::new();
         loop {
             let mut sequence = Vec::new();
-            if reader.read_until('\n' as u8, &mut sequence)? == 0 {
+            if reader.read_until(b'\n', &mut sequence)? == 0 {
                 break;
             }
 
+            let length = strip_trailing(&sequence, b'\n').len();
+            sequence.truncate(length);
             sequences.push(sequence);
         }
 
@@ -43,14 +46,13 @@ fn main() -> io::Result<()> {
         options.input.into_iter().map(|s| s.into_bytes()).collect()
     };
 
-    let sequences: Vec<&[u8]> = owned_sequences.iter().map(|ref v| {
-        let slice = v.as_slice();
-        &slice[..slice.len() - 1]
-    }).collect();
+    let alphabet = options.alphabet.as_ref().map(|s| Alphabet::new(s.as_bytes()));
+    let owned_tree = SuffixTree::from_sequences_owned(owned_sequences, alphabet);
+    let tree = owned_tree.tree();
 
-    let alphabet = options.alphabet.as_ref().map(|ref s| Alphabet::new(s.as_bytes()));
-    match longest_common_subsequence(&sequences, alphabet) {
-        Some(sequence) => {
+    match tree.longest_common_subsequence().next() {
+        Some((seq_id, start, end)) => {
+            let sequence = &tree.sequence_by_id(seq_id)[start..end];
             let text = str::from_utf8(sequence).unwrap_or("<invalid_string>");
             println!("{}", text);
         },
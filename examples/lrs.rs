@@ -0,0 +1,58 @@
+extern crate structopt;
+extern crate suffix_tree;
+
+use std::fs::File;
+use std::io;
+use std::io::Read;
+use std::path::PathBuf;
+use std::str;
+use structopt::StructOpt;
+use suffix_tree::SuffixTree;
+use suffix_tree::alphabet::Alphabet;
+use suffix_tree::util::strip_trailing;
+
+#[derive(StructOpt)]
+struct Options {
+    #[structopt(short = "f", long = "file", parse(from_os_str))]
+    file_path: Option<PathBuf>,
+    #[structopt(short = "a", long = "alphabet")]
+    alphabet: Option<String>,
+    #[structopt(name = "INPUT")]
+    input: Option<String>,
+}
+
+fn main() -> io::Result<()> {
+    let options = Options::from_args();
+
+    let sequence: Vec<u8> = if let Some(file_path) = options.file_path {
+        let mut sequence = Vec::new();
+        File::open(file_path)?.read_to_end(&mut sequence)?;
+
+        let length = strip_trailing(&sequence, b'\n').len();
+        sequence.truncate(length);
+
+        sequence
+    } else {
+        options.input.unwrap_or_default().into_bytes()
+    };
+
+    let alphabet = options.alphabet.as_ref().map(|s| Alphabet::new(s.as_bytes()));
+    let tree = SuffixTree::from_sequence(&sequence, alphabet);
+
+    match tree.longest_k_repeat(0, 2) {
+        Some((start, end)) if start < end => {
+            let repeat = &sequence[start..end];
+            let text = str::from_utf8(repeat).unwrap_or("<invalid_string>");
+            println!("{}", text);
+
+            let mut positions: Vec<usize> = tree.find(repeat).map(|(_, start, _)| start).collect();
+            positions.sort();
+            for position in positions {
+                println!("{}-{}", position, position + repeat.len());
+            }
+        },
+        _ => println!("No repeated substring."),
+    };
+
+    Ok(())
+}
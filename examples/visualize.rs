@@ -1,4 +1,4 @@
-#[macro_use] extern crate structopt;
+extern crate structopt;
 extern crate suffix_tree;
 
 use structopt::StructOpt;
@@ -16,7 +16,7 @@ struct Options {
 fn main() {
     let options = Options::from_args();
 
-    let alphabet = options.alphabet.as_ref().map(|ref s| Alphabet::new(s.as_bytes()));
+    let alphabet = options.alphabet.as_ref().map(|s| Alphabet::new(s.as_bytes()));
     let sequences: Vec<&[u8]> = options.strings.iter().map(|s| s.as_bytes()).collect();
 
     let output = SuffixTree::from_sequences(&sequences, alphabet).pretty_print();
@@ -1,37 +1,249 @@
+use core::fmt;
+
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AlphabetError {
+    DuplicateSymbol(u8),
+    UnknownSymbol(u8),
+}
+
+impl fmt::Display for AlphabetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AlphabetError::DuplicateSymbol(symbol) => {
+                write!(f, "symbol {:?} appears twice in alphabet", symbol as char)
+            },
+            AlphabetError::UnknownSymbol(symbol) => {
+                write!(f, "symbol {:?} is not part of the alphabet", symbol as char)
+            },
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for AlphabetError {}
+
+// The table is kept inline, boxing it would add an indirection to every
+// lookup during construction.
+#[allow(clippy::large_enum_variant)]
+#[derive(Clone)]
+enum Ranks {
+    Table([Option<u8>; 255]),
+    Fn(fn(u8) -> Option<u8>, fn(u8) -> u8),
+    // The ranks and the symbols by rank, for alphabets that own their symbols.
+    Owned([Option<u8>; 255], [u8; 255]),
+}
+
 #[derive(Clone)]
 pub struct Alphabet<'a> {
     pub size: u8,
-    pub symbols: &'a [u8],
-    ranks: [Option<u8>; 255],
+    symbols: &'a [u8],
+    ranks: Ranks,
 }
 
 impl<'a> Alphabet<'a> {
+    /// Creates an alphabet with symbols ranked in the given order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a symbol appears more than once or is byte 255, see
+    /// `try_new` for a non-panicking alternative.
     pub fn new(symbols: &'a [u8]) -> Alphabet<'a> {
+        match Alphabet::try_new(symbols) {
+            Ok(alphabet) => alphabet,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Creates an alphabet with symbols ranked in the given order, or returns
+    /// an error if a symbol appears more than once or is byte 255, which no
+    /// alphabet can hold, see `bytes`.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::alphabet::{Alphabet, AlphabetError};
+    ///
+    /// assert!(Alphabet::try_new(b"ACGT").is_ok());
+    /// assert_eq!(Alphabet::try_new(b"ACGA").err(), Some(AlphabetError::DuplicateSymbol(b'A')));
+    /// assert_eq!(Alphabet::try_new(&[0, 255]).err(), Some(AlphabetError::UnknownSymbol(255)));
+    /// ```
+    pub fn try_new(symbols: &'a [u8]) -> Result<Alphabet<'a>, AlphabetError> {
         let mut ranks = [None; 255];
         for (i, &symbol) in symbols.iter().enumerate() {
-            assert!(ranks[symbol as usize].is_none(), "symbol appears twice in alphabet");
+            if symbol == 255 {
+                return Err(AlphabetError::UnknownSymbol(symbol));
+            }
+            if ranks[symbol as usize].is_some() {
+                return Err(AlphabetError::DuplicateSymbol(symbol));
+            }
             ranks[symbol as usize] = Some(i as u8);
         }
 
-        Alphabet {
+        Ok(Alphabet {
             size: symbols.len() as u8,
             symbols,
-            ranks,
+            ranks: Ranks::Table(ranks),
+        })
+    }
+
+    /// Creates an alphabet of `size` symbols whose ranks are computed by
+    /// `rank_fn` instead of being looked up in a table. `symbol_fn` has to be
+    /// the inverse of `rank_fn` on the ranks `0..size`. The functions are
+    /// called through pointers, so this saves the table of `new` but does not
+    /// make lookups faster.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    /// use suffix_tree::alphabet::Alphabet;
+    ///
+    /// let dna = Alphabet::from_fn(
+    ///     4,
+    ///     |symbol| match symbol {
+    ///         b'A' => Some(0), b'C' => Some(1), b'G' => Some(2), b'T' => Some(3),
+    ///         _ => None,
+    ///     },
+    ///     |rank| b"ACGT"[rank as usize],
+    /// );
+    ///
+    /// assert_eq!(dna.rank_of_symbol(b'G'), 2);
+    /// assert_eq!(dna.symbol_of_rank(3), b'T');
+    ///
+    /// let tree = SuffixTree::from_sequence(b"GATTACA", Some(dna));
+    /// assert_eq!(tree.find(b"TA").count(), 1);
+    /// ```
+    pub fn from_fn(size: u8, rank_fn: fn(u8) -> Option<u8>, symbol_fn: fn(u8) -> u8) -> Alphabet<'a> {
+        Alphabet {
+            size,
+            symbols: &[],
+            ranks: Ranks::Fn(rank_fn, symbol_fn),
+        }
+    }
+
+    /// Creates an alphabet of all bytes except 255, ranked by their value. The
+    /// size of an alphabet is stored in a `u8`, which leaves no room for the
+    /// last byte.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::alphabet::Alphabet;
+    ///
+    /// let bytes = Alphabet::bytes();
+    /// assert_eq!(bytes.size, 255);
+    /// assert_eq!(bytes.rank_of_symbol(b'A'), 65);
+    /// assert!(!bytes.contains(255));
+    /// ```
+    pub fn bytes() -> Alphabet<'a> {
+        Alphabet::from_fn(
+            255,
+            |symbol| if symbol < 255 { Some(symbol) } else { None },
+            |rank| rank,
+        )
+    }
+
+    /// Creates the smallest alphabet containing every byte of the data, ranked
+    /// by their value. Fails if the data contains byte 255, which no alphabet
+    /// can hold, see `bytes`.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::alphabet::{Alphabet, AlphabetError};
+    ///
+    /// let alphabet = Alphabet::infer(b"GATTACA").unwrap();
+    /// assert_eq!(alphabet.size, 4);
+    /// assert_eq!(alphabet.rank_of_symbol(b'G'), 2);
+    /// assert_eq!(alphabet.symbol_of_rank(3), b'T');
+    ///
+    /// assert_eq!(Alphabet::infer(&[0, 255]).err(), Some(AlphabetError::UnknownSymbol(255)));
+    /// ```
+    pub fn infer(data: &[u8]) -> Result<Alphabet<'static>, AlphabetError> {
+        let mut present = [false; 256];
+        for &symbol in data {
+            present[symbol as usize] = true;
+        }
+
+        if present[255] {
+            return Err(AlphabetError::UnknownSymbol(255));
+        }
+
+        let mut ranks = [None; 255];
+        let mut symbols = [0; 255];
+        let mut size = 0;
+        for symbol in 0..255 {
+            if present[symbol as usize] {
+                ranks[symbol as usize] = Some(size);
+                symbols[size as usize] = symbol;
+                size += 1;
+            }
+        }
+
+        Ok(Alphabet {
+            size,
+            symbols: &[],
+            ranks: Ranks::Owned(ranks, symbols),
+        })
+    }
+
+    /// Returns true when the symbol is part of the alphabet.
+    pub fn contains(&self, symbol: u8) -> bool {
+        self.try_rank_of_symbol(symbol).is_some()
+    }
+
+    /// Returns the rank of the symbol, or `None` if it is not part of the
+    /// alphabet.
+    pub fn try_rank_of_symbol(&self, symbol: u8) -> Option<u8> {
+        match self.ranks {
+            Ranks::Table(ref ranks) | Ranks::Owned(ref ranks, _) => {
+                ranks.get(symbol as usize).and_then(|&rank| rank)
+            },
+            Ranks::Fn(rank_fn, _) => rank_fn(symbol),
         }
     }
 
     pub fn rank_of_symbol(&self, symbol: u8) -> u8 {
-        self.ranks[symbol as usize].unwrap()
+        match self.ranks {
+            Ranks::Table(ref ranks) | Ranks::Owned(ref ranks, _) => ranks[symbol as usize].unwrap(),
+            Ranks::Fn(rank_fn, _) => rank_fn(symbol).unwrap(),
+        }
+    }
+
+    /// Returns the symbols of the alphabet in the order of their rank.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::alphabet::Alphabet;
+    ///
+    /// assert_eq!(Alphabet::new(b"TGCA").symbols().collect::<Vec<_>>(), b"TGCA");
+    /// assert_eq!(Alphabet::infer(b"GATTACA").unwrap().symbols().collect::<Vec<_>>(), b"ACGT");
+    /// ```
+    pub fn symbols<'s>(&'s self) -> impl Iterator<Item = u8> + 's {
+        (0..self.size).map(move |rank| self.symbol_of_rank(rank))
     }
 
     pub fn symbol_of_rank(&self, rank: u8) -> u8 {
-        self.symbols[rank as usize]
+        match self.ranks {
+            Ranks::Table(_) => self.symbols[rank as usize],
+            Ranks::Fn(_, symbol_fn) => symbol_fn(rank),
+            Ranks::Owned(_, ref symbols) => symbols[rank as usize],
+        }
     }
 }
 
+const ASCII_SYMBOLS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+
+#[cfg(feature = "std")]
 lazy_static! {
     pub static ref ASCII_LOWERCASE: Alphabet<'static> = Alphabet::new(b"abcdefghijklmnopqrstuvwxyz");
     pub static ref ASCII_UPPERCASE: Alphabet<'static> = Alphabet::new(b"ABCDEFGHIJKLMNOPQRSTUVWXYZ");
-    pub static ref ASCII: Alphabet<'static> =
-        Alphabet::new(b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ");
+    pub static ref ASCII: Alphabet<'static> = Alphabet::new(ASCII_SYMBOLS);
+}
+
+/// Returns the alphabet used when none is given, which is `ASCII`.
+#[cfg(feature = "std")]
+pub(crate) fn default_alphabet<'a>() -> Alphabet<'a> {
+    ASCII.clone()
+}
+
+#[cfg(not(feature = "std"))]
+pub(crate) fn default_alphabet<'a>() -> Alphabet<'a> {
+    Alphabet::new(ASCII_SYMBOLS)
 }
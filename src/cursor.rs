@@ -0,0 +1,74 @@
+use {Node, NodeId, Occurences, Symbol, SuffixTree};
+
+/// A position in a suffix tree reached by matching a pattern from the root.
+///
+/// The cursor points into the edge leading to `node`, `offset` bytes past the
+/// start of the edge label.
+#[derive(Clone)]
+pub struct Cursor<'t, 'a: 't, 'b: 't> {
+    tree: &'t SuffixTree<'a, 'b>,
+    node: NodeId,
+    offset: usize,
+    depth: usize,
+}
+
+impl<'t, 'a, 'b> Cursor<'t, 'a, 'b> {
+    pub(crate) fn new(tree: &'t SuffixTree<'a, 'b>, node: NodeId, offset: usize, depth: usize)
+        -> Cursor<'t, 'a, 'b>
+    {
+        Cursor { tree, node, offset, depth }
+    }
+
+    /// Extends the matched pattern by one byte. Returns false and leaves the
+    /// cursor unchanged when the extended pattern is not contained in the tree.
+    pub fn step(&mut self, byte: u8) -> bool {
+        if self.tree.max_depth.is_some_and(|max_depth| self.depth >= max_depth) {
+            return false;
+        }
+
+        let label = self.tree.edge_label(self.node);
+
+        if self.offset < label.len() {
+            if label[self.offset] != byte {
+                return false;
+            }
+
+            self.offset += 1;
+        } else {
+            if let Node::Leaf(_) = self.tree.nodes[self.node] {
+                return false;
+            }
+
+            match self.tree.get_child(self.node, Symbol::Regular(byte)) {
+                Some(child) => {
+                    self.node = child;
+                    self.offset = 1;
+                },
+                None => return false,
+            }
+        }
+
+        self.depth += 1;
+        true
+    }
+
+    /// Returns the node whose incoming edge the cursor points into.
+    pub fn node(&self) -> NodeId {
+        self.node
+    }
+
+    /// Returns how far the cursor points into the edge leading to `node`.
+    pub fn offset(&self) -> usize {
+        self.offset
+    }
+
+    /// Returns the length of the pattern matched so far.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns all the occurences of the pattern matched so far.
+    pub fn occurences(&self) -> Occurences<'t> {
+        self.tree.occurences_of_node(self.node, self.offset, self.depth)
+    }
+}
@@ -0,0 +1,72 @@
+use alloc::vec::Vec;
+use {SequenceId, SuffixTree};
+
+/// The strand on which a match was found.
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq, Ord, PartialOrd)]
+pub enum Strand {
+    /// The pattern itself occurs in the sequence.
+    Forward,
+    /// The reverse complement of the pattern occurs in the sequence.
+    Reverse,
+}
+
+/// Returns the complement of a nucleotide. Symbols other than `A`, `C`, `G`
+/// and `T` in either case are returned unchanged.
+pub fn complement(symbol: u8) -> u8 {
+    match symbol {
+        b'A' => b'T',
+        b'T' => b'A',
+        b'G' => b'C',
+        b'C' => b'G',
+        b'a' => b't',
+        b't' => b'a',
+        b'g' => b'c',
+        b'c' => b'g',
+        _ => symbol,
+    }
+}
+
+/// Returns the reverse complement of a DNA sequence.
+///
+/// #Examples
+/// ```
+/// use suffix_tree::dna::reverse_complement;
+///
+/// assert_eq!(reverse_complement(b"GATTACA"), b"TGTAATC");
+/// ```
+pub fn reverse_complement(sequence: &[u8]) -> Vec<u8> {
+    sequence.iter().rev().map(|&symbol| complement(symbol)).collect()
+}
+
+impl<'a, 'b> SuffixTree<'a, 'b> {
+    /// Returns the occurences of the given pattern and of its reverse
+    /// complement, tagged with the strand they were found on. Palindromic
+    /// patterns are reported once per strand. Since only `A`, `C`, `G` and `T`
+    /// are complemented, for other alphabets this degenerates to also searching
+    /// for the reversed pattern.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    /// use suffix_tree::alphabet::Alphabet;
+    /// use suffix_tree::dna::Strand;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"AACGTT", Some(Alphabet::new(b"ACGT")));
+    /// let mut occurences = tree.find_with_revcomp(b"AAC");
+    /// occurences.sort();
+    ///
+    /// assert_eq!(occurences, vec![(0, 0, 3, Strand::Forward), (0, 3, 6, Strand::Reverse)]);
+    /// ```
+    pub fn find_with_revcomp(&self, pattern: &[u8]) -> Vec<(SequenceId, usize, usize, Strand)> {
+        let revcomp = reverse_complement(pattern);
+
+        let forward = self.find(pattern).map(|(seq_id, start, end)| {
+            (seq_id, start, end, Strand::Forward)
+        });
+        let reverse = self.find(&revcomp).map(|(seq_id, start, end)| {
+            (seq_id, start, end, Strand::Reverse)
+        });
+
+        forward.chain(reverse).collect()
+    }
+}
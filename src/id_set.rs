@@ -0,0 +1,91 @@
+use core::mem;
+use smallvec::SmallVec;
+
+use SequenceId;
+
+/// A set of sequence ids that grows with the largest id it contains. Up to 128
+/// ids are stored inline, so that small trees do not allocate per node.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub(crate) struct SequenceIdSet {
+    words: SmallVec<[u64; 2]>,
+}
+
+impl SequenceIdSet {
+    pub(crate) fn new() -> SequenceIdSet {
+        SequenceIdSet::default()
+    }
+
+    pub(crate) fn singleton(seq_id: SequenceId) -> SequenceIdSet {
+        let mut id_set = SequenceIdSet::new();
+        id_set.insert(seq_id);
+        id_set
+    }
+
+    /// Returns the set containing the ids `0..count`.
+    pub(crate) fn full(count: usize) -> SequenceIdSet {
+        let mut words: SmallVec<[u64; 2]> = smallvec![u64::MAX; count / 64];
+        if !count.is_multiple_of(64) {
+            words.push(u64::MAX >> (64 - count % 64));
+        }
+
+        SequenceIdSet { words }
+    }
+
+    pub(crate) fn insert(&mut self, seq_id: SequenceId) {
+        let word = seq_id / 64;
+        if word >= self.words.len() {
+            self.words.resize(word + 1, 0);
+        }
+
+        self.words[word] |= 1 << (seq_id % 64);
+    }
+
+    pub(crate) fn contains(&self, seq_id: SequenceId) -> bool {
+        self.words.get(seq_id / 64).is_some_and(|word| word & (1 << (seq_id % 64)) != 0)
+    }
+
+    pub(crate) fn union_with(&mut self, other: &SequenceIdSet) {
+        if other.words.len() > self.words.len() {
+            self.words.resize(other.words.len(), 0);
+        }
+
+        for (word, other_word) in self.words.iter_mut().zip(other.words.iter()) {
+            *word |= other_word;
+        }
+    }
+
+    pub(crate) fn is_superset(&self, other: &SequenceIdSet) -> bool {
+        other.words.iter().enumerate().all(|(i, &other_word)| {
+            let word = self.words.get(i).cloned().unwrap_or(0);
+            word & other_word == other_word
+        })
+    }
+
+    pub(crate) fn is_empty(&self) -> bool {
+        self.words.iter().all(|&word| word == 0)
+    }
+
+    pub(crate) fn len(&self) -> usize {
+        self.words.iter().map(|word| word.count_ones() as usize).sum()
+    }
+
+    pub(crate) fn shrink_to_fit(&mut self) {
+        self.words.shrink_to_fit();
+    }
+
+    /// Returns the memory allocated on the heap, which is none while the ids
+    /// fit inline.
+    pub(crate) fn heap_bytes(&self) -> usize {
+        if self.words.spilled() {
+            self.words.capacity() * mem::size_of::<u64>()
+        } else {
+            0
+        }
+    }
+
+    pub(crate) fn iter<'s>(&'s self) -> impl Iterator<Item = SequenceId> + 's {
+        self.words.iter().enumerate().flat_map(|(i, &word)| {
+            (0..64).filter(move |bit| word & (1 << bit) != 0).map(move |bit| i * 64 + bit)
+        })
+    }
+}
@@ -0,0 +1,210 @@
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use id_set::SequenceIdSet;
+use {ChildMap, InternalNode, LeafNode, Node, NodeId, SuffixTree};
+
+impl<'a, 'b> SuffixTree<'a, 'b> {
+    /// Verifies the structural invariants of the tree and describes the first
+    /// violation found. Meant for tests and fuzzing of the construction.
+    ///
+    /// The following is checked:
+    /// - every internal node has at least two children and a non-empty edge,
+    /// - children keyed by a terminal are leaves ending with that sequence,
+    ///   and only truncated trees have leaves kept apart from the keyed ones,
+    /// - suffix links point to the root or to internal nodes,
+    /// - every node knows the parent it is reachable from,
+    /// - leaves start within their sequence and spell out the suffix they
+    ///   stand for, which is compared by hash to stay linear in the size of
+    ///   the tree,
+    /// - every node is reachable exactly once from the root,
+    /// - the sequence id sets match the leaves below each internal node.
+    ///
+    /// The string depths and sequence id sets are computed by
+    /// `SuffixTreeBuilder::build`, so they are only checked if they cover
+    /// every node, which is not the case for the tree of a builder.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"mississippi", b"missouri"], None);
+    /// assert_eq!(tree.check_invariants(), Ok(()));
+    ///
+    /// let builder = SuffixTree::builder_from_sequence(b"mississippi", None);
+    /// assert_eq!(builder.tree().check_invariants(), Ok(()));
+    /// ```
+    pub fn check_invariants(&self) -> Result<(), String> {
+        fn check_children(tree: &SuffixTree, node: NodeId, children: &ChildMap) -> Result<(), String> {
+            for (&key, &child) in &children.terminals {
+                let data = match tree.sequences.get(key) {
+                    Some(sequence) => sequence.data,
+                    None => return Err(format!("Node {} has a child for unknown terminal {}.", node, key)),
+                };
+
+                match tree.nodes[child] {
+                    Node::Leaf(LeafNode { seq_id, start, .. }) if seq_id == key && start == data.len() => {},
+                    _ => return Err(format!("Node {} has a child for terminal {} that does not end it.", node, key)),
+                }
+            }
+
+            if tree.max_depth.is_none() && !children.truncated.is_empty() {
+                return Err(format!("Node {} has truncated leaves in an untruncated tree.", node));
+            }
+
+            Ok(())
+        }
+
+        // The path to a node is compared with the suffixes of its leaves by a
+        // polynomial hash modulo a Mersenne prime, which takes time linear in
+        // the number of nodes rather than in the total length of all suffixes.
+        const MODULUS: u64 = (1 << 61) - 1;
+        const BASE: u64 = 257;
+
+        fn mul(a: u64, b: u64) -> u64 {
+            (a as u128 * b as u128 % MODULUS as u128) as u64
+        }
+
+        // Indexed by length, so `powers[i]` is the base to the power of `i`.
+        let max_length = self.sequences.iter().map(|sequence| sequence.data.len()).max().unwrap_or(0);
+        let mut powers = vec![1; max_length + 1];
+        for i in 1..powers.len() {
+            powers[i] = mul(powers[i - 1], BASE);
+        }
+
+        // Indexed by sequence id and length, the hashes of all prefixes.
+        let prefix_hashes: Vec<Vec<u64>> = self.sequences.iter().map(|sequence| {
+            let mut hashes = Vec::with_capacity(sequence.data.len() + 1);
+            hashes.push(0);
+            for &byte in sequence.data {
+                let hash = (mul(*hashes.last().unwrap(), BASE) + byte as u64 + 1) % MODULUS;
+                hashes.push(hash);
+            }
+
+            hashes
+        }).collect();
+
+        let hash = |seq_id: usize, start: usize, end: usize| {
+            let hashes = &prefix_hashes[seq_id];
+            (hashes[end] + MODULUS - mul(hashes[start], powers[end - start])) % MODULUS
+        };
+
+        // A node is entered before its children and left after them, which is
+        // when the sequence ids collected from the children are compared.
+        enum Visit {
+            Enter(NodeId, NodeId),
+            Leave(NodeId),
+        }
+
+        let has_string_depths = self.string_depths.len() == self.nodes.len();
+        let has_id_sets = self.sequence_id_sets.len() == self.nodes.len();
+
+        let mut visited = vec![false; self.nodes.len()];
+        visited[0] = true;
+        check_children(self, 0, &self.root_node().children)?;
+
+        let mut stack: Vec<Visit> = self.root_node().children.iter().map(|child| Visit::Enter(child, 0)).collect();
+        stack.reverse();
+        // The string depth and path hash of every internal node on the current
+        // path, and the sequence ids found below it so far.
+        let mut paths = vec![(0, 0)];
+        let mut id_sets = vec![SequenceIdSet::new()];
+
+        while let Some(visit) = stack.pop() {
+            let node = match visit {
+                Visit::Enter(node, parent) => {
+                    if visited[node] {
+                        return Err(format!("Node {} is reachable more than once.", node));
+                    }
+                    visited[node] = true;
+
+                    if self.nodes[node].parent() != Some(parent) {
+                        return Err(format!("Node {} has a wrong parent.", node));
+                    }
+
+                    node
+                },
+                Visit::Leave(node) => {
+                    let id_set = id_sets.pop().unwrap();
+                    if has_id_sets && self.sequence_id_sets[node] != id_set {
+                        return Err(format!("Node {} has a wrong sequence id set.", node));
+                    }
+
+                    paths.pop();
+                    id_sets.last_mut().unwrap().union_with(&id_set);
+                    continue;
+                },
+            };
+
+            let (parent_depth, parent_hash) = *paths.last().unwrap();
+            match self.nodes[node] {
+                Node::Root(_) => return Err("Root is the child of another node.".to_string()),
+                Node::Internal(InternalNode {
+                    seq_id,
+                    start,
+                    end,
+                    ref children,
+                    ..
+                }) => {
+                    if start >= end || end > self.sequences[seq_id].data.len() {
+                        return Err(format!("Internal node {} has an invalid edge.", node));
+                    }
+
+                    let depth = parent_depth + end - start;
+                    if has_string_depths && self.string_depths[node] != depth {
+                        return Err(format!("Node {} has a wrong string depth.", node));
+                    }
+
+                    if let Some(link) = self.suffix_link(node) {
+                        if let Node::Leaf(_) = self.nodes[link] {
+                            return Err(format!("Suffix link of node {} points to a leaf.", node));
+                        }
+                    }
+
+                    check_children(self, node, children)?;
+
+                    if children.iter().count() < 2 {
+                        return Err(format!("Internal node {} has fewer than two children.", node));
+                    }
+
+                    let path_hash = (mul(parent_hash, powers[end - start]) + hash(seq_id, start, end)) % MODULUS;
+                    paths.push((depth, path_hash));
+                    id_sets.push(SequenceIdSet::new());
+
+                    // Children are pushed in reverse so they are visited in order.
+                    stack.push(Visit::Leave(node));
+                    let first = stack.len();
+                    stack.extend(children.iter().map(|child| Visit::Enter(child, node)));
+                    stack[first..].reverse();
+                },
+                Node::Leaf(LeafNode { seq_id, start, .. }) => {
+                    let data = match self.sequences.get(seq_id) {
+                        Some(sequence) => sequence.data,
+                        None => return Err(format!("Leaf {} has an unknown sequence.", node)),
+                    };
+
+                    if start > data.len() || start < parent_depth {
+                        return Err(format!("Leaf {} starts outside of its sequence.", node));
+                    }
+
+                    // The string depth of a leaf counts its terminal symbol.
+                    if has_string_depths && self.string_depths[node] != parent_depth + data.len() - start + 1 {
+                        return Err(format!("Node {} has a wrong string depth.", node));
+                    }
+
+                    // The edge of the leaf is the rest of its suffix by
+                    // construction, so only the path above it is compared.
+                    if hash(seq_id, start - parent_depth, start) != parent_hash {
+                        return Err(format!("Leaf {} does not spell out its suffix.", node));
+                    }
+
+                    id_sets.last_mut().unwrap().insert(seq_id);
+                },
+            }
+        }
+
+        match visited.iter().position(|&visited| !visited) {
+            Some(node) => Err(format!("Node {} is not reachable from the root.", node)),
+            None => Ok(()),
+        }
+    }
+}
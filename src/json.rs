@@ -0,0 +1,114 @@
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt::Write;
+use core::str;
+use {leaf_position, LeafNode, Node, NodeId, SuffixTree};
+
+impl<'a, 'b> SuffixTree<'a, 'b> {
+    /// Returns a nested JSON representation of the suffix tree. Every node
+    /// is an object with its incoming `edge` label, internal nodes and the
+    /// root carry their `children`, and leaves carry the `seq_id` and `start`
+    /// position of their suffix. Bytes that are not valid UTF-8 are written
+    /// as `\xNN` escapes inside the label.
+    ///
+    /// Like the labels, the `start` of a leaf refers to the stored bytes, so
+    /// in a reversed tree it is a position in the reversed sequence.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"ab", None);
+    /// assert_eq!(
+    ///     tree.to_json(),
+    ///     concat!(
+    ///         r#"{"edge":"","children":["#,
+    ///         r#"{"edge":"","seq_id":0,"start":2},"#,
+    ///         r#"{"edge":"ab","seq_id":0,"start":0},"#,
+    ///         r#"{"edge":"b","seq_id":0,"start":1}]}"#,
+    ///     )
+    /// );
+    /// ```
+    pub fn to_json(&self) -> String {
+        // The closing brackets of a node are written once all of its children
+        // have been popped off the stack.
+        enum Step {
+            Node(NodeId, usize, bool),
+            Close,
+        }
+
+        let mut output = String::new();
+        let mut stack = vec![Step::Node(0, 0, true)];
+        while let Some(step) = stack.pop() {
+            let (node, depth, is_first) = match step {
+                Step::Node(node, depth, is_first) => (node, depth, is_first),
+                Step::Close => {
+                    output.push_str("]}");
+                    continue;
+                },
+            };
+
+            if !is_first {
+                output.push(',');
+            }
+            output.push_str("{\"edge\":");
+            write_json_string(self.edge_label(node), &mut output);
+
+            match self.nodes[node] {
+                Node::Leaf(LeafNode { seq_id, start, .. }) => {
+                    write!(output, ",\"seq_id\":{},\"start\":{}}}", seq_id, leaf_position(seq_id, start, depth)).unwrap();
+                },
+                Node::Root(_) | Node::Internal(_) => {
+                    let depth = depth + self.edge_len(node);
+                    let children: Vec<NodeId> = self.children(node).collect();
+
+                    output.push_str(",\"children\":[");
+                    stack.push(Step::Close);
+                    for (i, &child) in children.iter().enumerate().rev() {
+                        stack.push(Step::Node(child, depth, i == 0));
+                    }
+                },
+            }
+        }
+
+        output
+    }
+}
+
+fn write_json_string(mut bytes: &[u8], output: &mut String) {
+    output.push('"');
+
+    loop {
+        let (valid, invalid) = match str::from_utf8(bytes) {
+            Ok(text) => (text, &[][..]),
+            Err(error) => {
+                let (valid, rest) = bytes.split_at(error.valid_up_to());
+                let invalid_len = error.error_len().unwrap_or(rest.len());
+                (str::from_utf8(valid).unwrap(), &rest[..invalid_len])
+            },
+        };
+
+        for c in valid.chars() {
+            match c {
+                '"' => output.push_str("\\\""),
+                '\\' => output.push_str("\\\\"),
+                '\n' => output.push_str("\\n"),
+                '\r' => output.push_str("\\r"),
+                '\t' => output.push_str("\\t"),
+                c if (c as u32) < 0x20 => write!(output, "\\u{:04x}", c as u32).unwrap(),
+                c => output.push(c),
+            }
+        }
+
+        for byte in invalid {
+            write!(output, "\\\\x{:02x}", byte).unwrap();
+        }
+
+        bytes = &bytes[(valid.len() + invalid.len())..];
+        if bytes.is_empty() {
+            break;
+        }
+    }
+
+    output.push('"');
+}
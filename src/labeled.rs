@@ -0,0 +1,84 @@
+use alloc::vec::Vec;
+use {SequenceId, SuffixTree, SuffixTreeBuilder};
+
+/// A builder that tags every sequence with a label of type `L`, like a file
+/// name or the corpus it came from, created by `SuffixTreeBuilder::labeled`.
+///
+/// The tree itself still identifies sequences by their numeric ids, which
+/// keeps queries as cheap as before. The labels are kept in a side table
+/// indexed by those ids.
+pub struct LabeledSuffixTreeBuilder<'a, 'b, L> {
+    builder: SuffixTreeBuilder<'a, 'b>,
+    labels: Vec<L>,
+}
+
+impl<'a, 'b, L> LabeledSuffixTreeBuilder<'a, 'b, L> {
+    /// Adds the sequence with the given label and returns its id.
+    ///
+    /// If the terminal policy makes the sequence share the id of an identical
+    /// one added before, the label of that sequence is kept and `label` is
+    /// dropped. Panics like `SuffixTreeBuilder::add_sequence`.
+    pub fn add_sequence_labeled(&mut self, sequence: &'a [u8], label: L) -> SequenceId {
+        let seq_id = match self.builder.try_add_sequence(sequence) {
+            Ok(seq_id) => seq_id,
+            Err(error) => panic!("{}", error),
+        };
+
+        if seq_id == self.labels.len() {
+            self.labels.push(label);
+        }
+
+        seq_id
+    }
+
+    pub fn build(self) -> LabeledSuffixTree<'a, 'b, L> {
+        LabeledSuffixTree { tree: self.builder.build(), labels: self.labels }
+    }
+}
+
+/// A suffix tree whose sequences carry labels, see `LabeledSuffixTreeBuilder`.
+///
+/// #Examples
+/// ```
+/// use suffix_tree::SuffixTreeBuilder;
+///
+/// let mut builder = SuffixTreeBuilder::new(None).labeled();
+/// builder.add_sequence_labeled(b"test", "a.txt".to_string());
+/// builder.add_sequence_labeled(b"rest", "b.txt".to_string());
+/// let labeled = builder.build();
+///
+/// let files: Vec<&str> = labeled.tree().find(b"rest")
+///     .map(|(seq_id, _, _)| labeled.label_of(seq_id).as_str())
+///     .collect();
+/// assert_eq!(files, vec!["b.txt"]);
+/// ```
+pub struct LabeledSuffixTree<'a, 'b, L> {
+    tree: SuffixTree<'a, 'b>,
+    labels: Vec<L>,
+}
+
+impl<'a, 'b, L> LabeledSuffixTree<'a, 'b, L> {
+    pub fn tree(&self) -> &SuffixTree<'a, 'b> {
+        &self.tree
+    }
+
+    /// Returns the label the sequence with the given id was added with.
+    pub fn label_of(&self, seq_id: SequenceId) -> &L {
+        &self.labels[seq_id]
+    }
+
+    /// Splits the labeled tree into the tree and its labels, indexed by
+    /// sequence id.
+    pub fn into_parts(self) -> (SuffixTree<'a, 'b>, Vec<L>) {
+        (self.tree, self.labels)
+    }
+}
+
+impl<'a, 'b> SuffixTreeBuilder<'a, 'b> {
+    /// Turns the builder into one that labels each added sequence. Sequences
+    /// added before have no label, so the builder has to be empty.
+    pub fn labeled<L>(self) -> LabeledSuffixTreeBuilder<'a, 'b, L> {
+        assert!(self.tree.sequences.is_empty(), "Only an empty builder can label its sequences.");
+        LabeledSuffixTreeBuilder { builder: self, labels: Vec::new() }
+    }
+}
@@ -1,17 +1,69 @@
+#![cfg_attr(not(feature = "std"), no_std)]
+
+#[cfg(not(any(feature = "std", feature = "hashbrown")))]
+compile_error!("Building without `std` requires the `hashbrown` feature.");
+
+#[macro_use]
+extern crate alloc;
+// Only injected into the crate root for `no_std` builds.
+#[cfg(feature = "std")]
+extern crate core;
+#[cfg(not(feature = "std"))]
+extern crate hashbrown;
+#[cfg(feature = "std")]
 #[macro_use]
 extern crate lazy_static;
 #[macro_use]
 extern crate smallvec;
 
 pub mod alphabet;
+pub mod dna;
+pub mod util;
+mod cursor;
+mod id_set;
+mod invariants;
+mod json;
+mod labeled;
+mod lz78;
+mod node_ref;
+mod owned;
+mod packed;
+mod patterns;
+mod query_cache;
+mod scan;
+mod tandem;
+mod truncate;
+mod windowed;
 
 use alphabet::Alphabet;
+use id_set::SequenceIdSet;
+pub use cursor::Cursor;
+pub use labeled::{LabeledSuffixTree, LabeledSuffixTreeBuilder};
+pub use node_ref::{NodeKind, NodeRef};
+pub use owned::OwnedSuffixTree;
+pub use packed::{unpack_occurence, PackingError, PACKED_START_BITS};
+pub use patterns::PreparedPatterns;
+pub use query_cache::QueryCache;
+pub use windowed::WindowedSuffixTree;
+use alloc::borrow::ToOwned;
+use alloc::boxed::Box;
+use alloc::collections::{BTreeMap, BinaryHeap, VecDeque};
+use alloc::rc::Rc;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::cmp::Reverse;
+use core::iter;
+use core::mem;
+use core::ops::{ControlFlow, Range};
+use core::str;
+#[cfg(not(feature = "std"))]
+use hashbrown::HashMap;
 use smallvec::SmallVec;
-use std::cell::Cell;
+#[cfg(feature = "std")]
 use std::collections::HashMap;
-use std::iter;
-use std::str;
-use std::u8;
+#[cfg(feature = "std")]
+use std::io::{self, BufRead};
 
 #[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
 enum Symbol {
@@ -21,17 +73,103 @@ enum Symbol {
 
 type SequenceId = usize;
 
+/// A sequence contains a symbol that is not part of the alphabet of the tree.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct InvalidSymbolError {
+    /// The id the sequence would have been added under.
+    pub seq_id: SequenceId,
+    /// The position of the first invalid symbol in the sequence.
+    pub position: usize,
+    pub symbol: u8,
+}
+
+impl fmt::Display for InvalidSymbolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "symbol {:?} at position {} of sequence {} is not part of the alphabet",
+            self.symbol as char, self.position, self.seq_id
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for InvalidSymbolError {}
+
+/// The reason a sequence could not be added to a tree.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub enum AddSequenceError {
+    InvalidSymbol(InvalidSymbolError),
+    /// The sequence is identical to the one with the given id, and duplicates
+    /// are rejected by `TerminalPolicy::Error`.
+    Duplicate(SequenceId),
+}
+
+impl From<InvalidSymbolError> for AddSequenceError {
+    fn from(error: InvalidSymbolError) -> AddSequenceError {
+        AddSequenceError::InvalidSymbol(error)
+    }
+}
+
+impl fmt::Display for AddSequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            AddSequenceError::InvalidSymbol(ref error) => error.fmt(f),
+            AddSequenceError::Duplicate(seq_id) => {
+                write!(f, "sequence is identical to sequence {}", seq_id)
+            },
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for AddSequenceError {}
+
+/// Counts of the steps taken by the construction, collected by builders
+/// created with `SuffixTreeBuilder::with_metrics`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub struct BuildMetrics {
+    /// The number of symbols inserted, including one terminal per sequence.
+    pub symbols_processed: usize,
+    /// The number of leaves created.
+    pub leaf_insertions: usize,
+    /// The number of edges split by a new internal node.
+    pub edge_splits: usize,
+    /// The number of times the active point moved along a suffix link.
+    pub suffix_link_follows: usize,
+}
+
+/// How a builder handles a sequence identical to one that was added before.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum TerminalPolicy {
+    /// Add the sequence under a new id with its own terminal symbol, so that
+    /// every occurence is reported once for each copy.
+    #[default]
+    Distinct,
+    /// Do not add the sequence again, copies share the id of the first one.
+    Shared,
+    /// Reject the sequence, see `AddSequenceError::Duplicate`.
+    Error,
+}
+
+// Edge labels, leaf suffixes and matches are handed out as slices borrowing
+// from the data.
 #[derive(Copy, Clone)]
 struct Sequence<'a> {
     id: SequenceId,
     data: &'a [u8],
+    // The sequence in the orientation positions are reported in, which only
+    // differs from `data` in reversed trees.
+    original: &'a [u8],
 }
 
 impl <'a> Sequence<'a> {
-    fn new(id: SequenceId, data: &'a [u8]) -> Sequence {
-        Sequence { id, data }
+    fn new(id: SequenceId, data: &'a [u8], original: &'a [u8]) -> Sequence<'a> {
+        Sequence { id, data, original }
     }
 
+    /// Returns the length including the terminal symbol. Positions reported to
+    /// users are always taken from `data` and never point past its end.
     fn len(&self) -> usize {
         self.data.len() + 1
     }
@@ -45,8 +183,8 @@ impl <'a> Sequence<'a> {
     }
 
     fn substring(&self, start: usize, maybe_end: Option<usize>) -> String {
-        let end = maybe_end.unwrap_or_else(|| self.data.len());
-        let substr = str::from_utf8(&self.data[start..end]).unwrap_or("<invalid_string>");
+        let end = maybe_end.unwrap_or(self.data.len());
+        let substr = escape_invalid_utf8(&self.data[start..end]);
 
         if maybe_end.is_none() {
             format!("{}${}", substr, self.id)
@@ -56,18 +194,60 @@ impl <'a> Sequence<'a> {
     }
 }
 
+/// Decodes the bytes as UTF-8, writing bytes that are not part of a valid
+/// character as `\xNN` escapes. Edges often split multi-byte characters, so
+/// this keeps the printed labels readable.
+fn escape_invalid_utf8(bytes: &[u8]) -> String {
+    let mut result = String::with_capacity(bytes.len());
+    for chunk in bytes.utf8_chunks() {
+        result.push_str(chunk.valid());
+        for byte in chunk.invalid() {
+            result.push_str(&format!("\\x{:02x}", byte));
+        }
+    }
+
+    result
+}
+
 type NodeId = usize;
 
+/// How the children of internal nodes are stored.
+#[derive(Debug, Copy, Clone, Eq, PartialEq, Default)]
+pub enum ChildStorage {
+    /// One slot per symbol of the alphabet, giving constant time lookups.
+    #[default]
+    Dense,
+    /// A list of the existing children sorted by symbol rank, which uses less
+    /// memory when nodes have few children compared to the alphabet size.
+    Sparse,
+}
+
+#[derive(Debug)]
+enum RegularChildren {
+    Dense(SmallVec<[Option<NodeId>; 4]>),
+    Sparse(SmallVec<[(u8, NodeId); 4]>),
+}
+
+#[derive(Debug)]
 struct ChildMap {
-    terminals: HashMap<usize, NodeId>,
-    regular: SmallVec<[Option<NodeId>; 4]>,
+    terminals: BTreeMap<usize, NodeId>,
+    regular: RegularChildren,
+    // Leaves below the maximum depth of a truncated tree, see `truncate`.
+    // Their edges do not start with a symbol that sets them apart.
+    truncated: Vec<NodeId>,
 }
 
 impl ChildMap {
-    fn new(alphabet_size: u8) -> ChildMap {
+    fn new(alphabet_size: u8, storage: ChildStorage) -> ChildMap {
+        let regular = match storage {
+            ChildStorage::Dense => RegularChildren::Dense(smallvec![None; alphabet_size as usize]),
+            ChildStorage::Sparse => RegularChildren::Sparse(SmallVec::new()),
+        };
+
         ChildMap {
-            terminals: HashMap::new(),
-            regular: smallvec![None; alphabet_size as usize],
+            terminals: BTreeMap::new(),
+            regular,
+            truncated: Vec::new(),
         }
     }
 
@@ -78,7 +258,17 @@ impl ChildMap {
             }
             Symbol::Regular(symbol) => {
                 let rank = alphabet.rank_of_symbol(symbol);
-                self.regular[rank as usize] = Some(child);
+                match self.regular {
+                    RegularChildren::Dense(ref mut children) => {
+                        children[rank as usize] = Some(child);
+                    },
+                    RegularChildren::Sparse(ref mut children) => {
+                        match children.binary_search_by_key(&rank, |&(rank, _)| rank) {
+                            Ok(index) => children[index].1 = child,
+                            Err(index) => children.insert(index, (rank, child)),
+                        }
+                    },
+                }
             }
         }
     }
@@ -86,39 +276,160 @@ impl ChildMap {
     fn get_child(&self, alphabet: &Alphabet, symbol: Symbol) -> Option<NodeId> {
         match symbol {
             Symbol::Terminal(seq_id) => self.terminals.get(&seq_id).cloned(),
-            Symbol::Regular(symbol) =>{
-                let rank = alphabet.rank_of_symbol(symbol);
-                self.regular[rank as usize]
-            }
+            Symbol::Regular(symbol) => self.get_regular_child(alphabet.try_rank_of_symbol(symbol)?),
+        }
+    }
+
+    fn get_regular_child(&self, rank: u8) -> Option<NodeId> {
+        match self.regular {
+            RegularChildren::Dense(ref children) => children.get(rank as usize).cloned().flatten(),
+            RegularChildren::Sparse(ref children) => {
+                children.binary_search_by_key(&rank, |&(rank, _)| rank).ok().map(|index| {
+                    children[index].1
+                })
+            },
+        }
+    }
+
+    fn add_truncated_leaf(&mut self, child: NodeId) {
+        self.truncated.push(child);
+    }
+
+    /// Removes all children while keeping the allocated storage.
+    fn clear(&mut self) {
+        self.terminals.clear();
+        self.truncated.clear();
+        match self.regular {
+            RegularChildren::Dense(ref mut children) => {
+                for child in children.iter_mut() {
+                    *child = None;
+                }
+            },
+            RegularChildren::Sparse(ref mut children) => children.clear(),
+        }
+    }
+
+    /// Calls `f` for every child in the same order as `iter`, without boxing,
+    /// until it returns `ControlFlow::Break`.
+    fn try_for_each<B, F>(&self, mut f: F) -> ControlFlow<B>
+    where
+        F: FnMut(NodeId) -> ControlFlow<B>
+    {
+        for &child in self.terminals.values() {
+            f(child)?;
+        }
+
+        match self.regular {
+            RegularChildren::Dense(ref children) => {
+                for &child in children.iter().flatten() {
+                    f(child)?;
+                }
+            },
+            RegularChildren::Sparse(ref children) => {
+                for &(_, child) in children.iter() {
+                    f(child)?;
+                }
+            },
+        }
+
+        for &child in &self.truncated {
+            f(child)?;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    fn storage(&self) -> ChildStorage {
+        match self.regular {
+            RegularChildren::Dense(_) => ChildStorage::Dense,
+            RegularChildren::Sparse(_) => ChildStorage::Sparse,
+        }
+    }
+
+    fn shrink_to_fit(&mut self) {
+        match self.regular {
+            RegularChildren::Dense(ref mut children) => children.shrink_to_fit(),
+            RegularChildren::Sparse(ref mut children) => children.shrink_to_fit(),
+        }
+        self.truncated.shrink_to_fit();
+    }
+
+    /// Returns the memory allocated on the heap. The nodes of the map of
+    /// terminal children are not visible, so only their entries are counted.
+    fn heap_bytes(&self) -> usize {
+        let terminals = self.terminals.len() * mem::size_of::<(usize, NodeId)>();
+        let regular = match self.regular {
+            RegularChildren::Dense(ref children) if children.spilled() => {
+                children.capacity() * mem::size_of::<Option<NodeId>>()
+            },
+            RegularChildren::Sparse(ref children) if children.spilled() => {
+                children.capacity() * mem::size_of::<(u8, NodeId)>()
+            },
+            _ => 0,
+        };
+
+        terminals + regular + self.truncated.capacity() * mem::size_of::<NodeId>()
+    }
+
+    /// Returns the ranks of the symbols that have a child, in ascending order.
+    fn regular_ranks(&self) -> Vec<u8> {
+        match self.regular {
+            RegularChildren::Dense(ref children) => {
+                (0..children.len() as u8).filter(|&rank| children[rank as usize].is_some()).collect()
+            },
+            RegularChildren::Sparse(ref children) => children.iter().map(|&(rank, _)| rank).collect(),
         }
     }
 
-    fn iter<'s>(&'s self) -> Box<Iterator<Item = NodeId> + 's> {
+    /// Returns the child that comes first in the order of `iter`.
+    fn first(&self) -> Option<NodeId> {
+        self.terminals.values().next().cloned().or_else(|| match self.regular {
+            RegularChildren::Dense(ref children) => children.iter().flatten().next().cloned(),
+            RegularChildren::Sparse(ref children) => children.first().map(|&(_, child)| child),
+        }).or_else(|| self.truncated.first().cloned())
+    }
+
+    /// Iterates over the children in lexicographic order of their edge labels.
+    /// Terminal symbols sort before all regular symbols and by sequence id
+    /// among each other, regular symbols sort by their rank in the alphabet.
+    /// The leaves of a truncated tree come last, in the order of their suffixes.
+    fn iter<'s>(&'s self) -> Box<dyn Iterator<Item = NodeId> + 's> {
         let terminals_iter = self.terminals.values().cloned();
-        let regular_iter = self.regular.iter().filter_map(|&v| v);
+        let truncated_iter = self.truncated.iter().cloned();
 
-        Box::new(terminals_iter.chain(regular_iter))
+        match self.regular {
+            RegularChildren::Dense(ref children) => {
+                Box::new(terminals_iter.chain(children.iter().filter_map(|&v| v)).chain(truncated_iter))
+            },
+            RegularChildren::Sparse(ref children) => {
+                Box::new(terminals_iter.chain(children.iter().map(|&(_, child)| child)).chain(truncated_iter))
+            },
+        }
     }
 }
 
+#[derive(Debug)]
 struct RootNode {
     children: ChildMap,
 }
 
+#[derive(Debug)]
 struct InternalNode {
     seq_id: SequenceId,
     start: usize,
     end: usize,
     children: ChildMap,
-    suffix_link: Option<NodeId>,
-    sequence_id_set: Cell<Option<u128>>,
+    parent: Option<NodeId>,
 }
 
+#[derive(Debug)]
 struct LeafNode {
     seq_id: SequenceId,
     start: usize,
+    parent: Option<NodeId>,
 }
 
+#[derive(Debug)]
 enum Node {
     Root(RootNode),
     Internal(InternalNode),
@@ -127,23 +438,37 @@ enum Node {
 
 impl Node {
     fn new_root(alphabet_size: u8) -> Node {
-        Node::Root(RootNode { children: ChildMap::new(alphabet_size) })
+        Node::Root(RootNode { children: ChildMap::new(alphabet_size, ChildStorage::Dense) })
     }
 
-    fn new_internal(alphabet_size: u8, seq_id: SequenceId, start: usize, end: usize) -> Node {
+    fn new_internal(children: ChildMap, seq_id: SequenceId, start: usize, end: usize) -> Node {
         Node::Internal(InternalNode {
             seq_id,
             start,
             end,
-            children: ChildMap::new(alphabet_size),
-            suffix_link: None,
-
-            sequence_id_set: Cell::new(None),
+            children,
+            parent: None,
         })
     }
 
     fn new_leaf(seq_id: SequenceId, start: usize) -> Node {
-        Node::Leaf(LeafNode { seq_id, start })
+        Node::Leaf(LeafNode { seq_id, start, parent: None })
+    }
+
+    fn parent(&self) -> Option<NodeId> {
+        match *self {
+            Node::Root(_) => None,
+            Node::Internal(InternalNode { parent, .. }) |
+            Node::Leaf(LeafNode { parent, .. }) => parent,
+        }
+    }
+
+    fn set_parent(&mut self, new_parent: NodeId) {
+        match *self {
+            Node::Root(_) => panic!("The root has no parent."),
+            Node::Internal(InternalNode { ref mut parent, .. }) |
+            Node::Leaf(LeafNode { ref mut parent, .. }) => *parent = Some(new_parent),
+        }
     }
 
     fn children(&self) -> Option<&ChildMap> {
@@ -173,29 +498,303 @@ impl Node {
     }
 
     fn is_leaf(&self) -> bool {
-        if let Node::Leaf(_) = *self {
-            true
-        } else {
-            false
+        matches!(*self, Node::Leaf(_))
+    }
+}
+
+/// An occurence of a pattern within one of the sequences of a suffix tree.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct Match<'a> {
+    seq_id: SequenceId,
+    start: usize,
+    end: usize,
+    data: &'a [u8],
+}
+
+impl<'a> Match<'a> {
+    pub fn seq_id(&self) -> SequenceId {
+        self.seq_id
+    }
+
+    pub fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Returns the matched part of the sequence.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        &self.data[self.start..self.end]
+    }
+}
+
+// Returns the position `depth` bytes before `start`, the start of an edge on
+// the path to a leaf of the sequence, which is where that path starts at the
+// node `depth` bytes above the edge. The edges on the path spell out part of
+// the suffix of the leaf, so this can only fail if the tree was constructed
+// incorrectly, in which case it panics with the details instead of
+// underflowing.
+fn leaf_position(seq_id: SequenceId, start: usize, depth: usize) -> usize {
+    start.checked_sub(depth).unwrap_or_else(|| {
+        panic!("A leaf edge of sequence {} starts at {}, less than the depth {} above it.", seq_id, start, depth)
+    })
+}
+
+// Returns the start of a match of the given length ending at `end`. Match ends
+// are derived from the leaves below the match, so like `leaf_position` this
+// can only fail for a tree that was constructed incorrectly.
+fn match_start(seq_id: SequenceId, end: usize, length: usize) -> usize {
+    end.checked_sub(length).unwrap_or_else(|| {
+        panic!("A match of length {} in sequence {} ends at {}.", length, seq_id, end)
+    })
+}
+
+/// Iterates over the leaves below a node with an explicit stack, yielding
+/// the sequence id of every leaf and the position in that sequence where
+/// the edge label of the starting node begins.
+struct SubtreeLeaves<'s> {
+    tree: &'s SuffixTree<'s, 's>,
+    stack: Vec<(NodeId, usize)>,
+}
+
+impl<'s> SubtreeLeaves<'s> {
+    fn new(tree: &'s SuffixTree<'s, 's>, node: NodeId, depth: usize) -> SubtreeLeaves<'s> {
+        SubtreeLeaves { tree, stack: vec![(node, depth)] }
+    }
+}
+
+impl<'s> Iterator for SubtreeLeaves<'s> {
+    type Item = (SequenceId, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        while let Some((node, depth)) = self.stack.pop() {
+            match self.tree.nodes[node] {
+                Node::Root(_) => {},
+                Node::Internal(InternalNode { start, end, ref children, .. }) => {
+                    // Children are pushed in reverse so they are visited in order.
+                    let first = self.stack.len();
+                    let stack = &mut self.stack;
+                    let _ = children.try_for_each(|child| {
+                        stack.push((child, depth + end - start));
+                        ControlFlow::<()>::Continue(())
+                    });
+                    self.stack[first..].reverse();
+                },
+                Node::Leaf(LeafNode { seq_id, start, .. }) => {
+                    return Some((seq_id, leaf_position(seq_id, start, depth)));
+                },
+            }
+        }
+
+        None
+    }
+}
+
+enum OccurenceSource<'s> {
+    Empty,
+    Subtree(SubtreeLeaves<'s>),
+    Positions(Rc<Vec<(SequenceId, usize)>>, usize),
+}
+
+/// An iterator over the occurences of a pattern, given as the sequence id and
+/// the start and end position of every occurence. The number of occurences is
+/// known upfront.
+pub struct Occurences<'s> {
+    source: OccurenceSource<'s>,
+    // The tree whose sequences are stored reversed, if they are.
+    reversed: Option<&'s SuffixTree<'s, 's>>,
+    offset: usize,
+    length: usize,
+    remaining: usize,
+}
+
+impl<'s> Occurences<'s> {
+    fn from_subtree(
+        tree: &'s SuffixTree<'s, 's>,
+        node: NodeId,
+        offset: usize,
+        length: usize
+    ) -> Occurences<'s> {
+        Occurences {
+            source: OccurenceSource::Subtree(SubtreeLeaves::new(tree, node, 0)),
+            reversed: if tree.reversed { Some(tree) } else { None },
+            offset,
+            length,
+            remaining: tree.leaf_counts[node],
         }
     }
+
+    /// Creates the occurences from the positions where the edge label
+    /// containing the end of the pattern starts.
+    fn from_positions(
+        tree: &'s SuffixTree<'s, 's>,
+        positions: Rc<Vec<(SequenceId, usize)>>,
+        offset: usize,
+        length: usize
+    ) -> Occurences<'s> {
+        Occurences {
+            remaining: positions.len(),
+            source: OccurenceSource::Positions(positions, 0),
+            reversed: if tree.reversed { Some(tree) } else { None },
+            offset,
+            length,
+        }
+    }
+
+    fn empty() -> Occurences<'s> {
+        Occurences { source: OccurenceSource::Empty, reversed: None, offset: 0, length: 0, remaining: 0 }
+    }
+}
+
+impl<'s> Iterator for Occurences<'s> {
+    type Item = (SequenceId, usize, usize);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let next = match self.source {
+            OccurenceSource::Empty => None,
+            OccurenceSource::Subtree(ref mut leaves) => leaves.next(),
+            OccurenceSource::Positions(ref positions, ref mut index) => {
+                let next = positions.get(*index).cloned();
+                *index += 1;
+                next
+            },
+        };
+
+        next.map(|(seq_id, position)| {
+            self.remaining -= 1;
+            let end = position + self.offset;
+            let start = match_start(seq_id, end, self.length);
+            match self.reversed {
+                Some(tree) => tree.original_range(seq_id, start, end),
+                None => (seq_id, start, end),
+            }
+        })
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'s> ExactSizeIterator for Occurences<'s> {}
+
+/// Controls how `SuffixTree::pretty_print_with` renders the tree.
+#[derive(Debug, Clone, Default)]
+pub struct PrettyPrintOptions {
+    /// Annotate each edge with the id and string depth of its target node.
+    pub show_node_ids: bool,
+    /// Truncate edge labels longer than this many characters with an ellipsis.
+    pub max_edge_len: Option<usize>,
+    /// Annotate internal nodes with the id of their suffix link target.
+    pub show_suffix_links: bool,
 }
 
 pub struct SuffixTree<'a, 'b> {
     alphabet: Alphabet<'b>,
     sequences: Vec<Sequence<'a>>,
     nodes: Vec<Node>, 
+    // Indexed by node id, empty once dropped by `shrink`.
+    suffix_links: Vec<Option<NodeId>>,
+    string_depths: Vec<usize>,
+    leaf_counts: Vec<usize>,
+    sequence_id_sets: Vec<SequenceIdSet>,
+    max_depth: Option<usize>,
+    // Whether the sequences are stored reversed, in which case reported
+    // positions are mapped back to the original orientation.
+    reversed: bool,
+    // Whether every sequence is stored twice in a row, so that patterns
+    // wrapping around its end can be found with `find_circular`.
+    circular: bool,
+}
+
+// Printing the nodes would be unreadable for all but the smallest trees, so
+// only their number is shown. `pretty_print` shows the whole tree.
+impl<'a, 'b> fmt::Debug for SuffixTree<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SuffixTree")
+            .field("node_count", &self.nodes.len())
+            .field("sequence_count", &self.sequences.len())
+            .field("alphabet_size", &self.alphabet.size)
+            .field("max_depth", &self.max_depth)
+            .finish()
+    }
+}
+
+/// Compares the structure of two trees, not their node ids, which depend on
+/// the order in which nodes were created. Two trees are equal if the children
+/// of corresponding nodes have the same edge labels, and corresponding leaves
+/// stand for the same suffix of the same sequence. The trees have to share the
+/// order of their alphabets, as children are compared in that order.
+///
+/// #Examples
+/// ```
+/// use suffix_tree::{ChildStorage, SuffixTree, SuffixTreeBuilder};
+///
+/// let tree = SuffixTree::from_sequences(&[b"test", b"rest"], None);
+///
+/// let mut builder = SuffixTree::from_sequence(b"test", None).resume()
+///     .with_child_storage(ChildStorage::Sparse);
+/// builder.add_sequence(b"rest");
+/// assert!(builder.build() == tree);
+///
+/// assert!(SuffixTree::from_sequences(&[b"rest", b"test"], None) != tree);
+/// ```
+impl<'a, 'b, 'c, 'd> PartialEq<SuffixTree<'c, 'd>> for SuffixTree<'a, 'b> {
+    fn eq(&self, other: &SuffixTree<'c, 'd>) -> bool {
+        if self.sequences.len() != other.sequences.len()
+            || self.reversed != other.reversed
+            || self.circular != other.circular
+        {
+            return false;
+        }
+
+        // Corresponding nodes are walked in pairs, so the order in which the
+        // pairs are compared does not matter.
+        let mut stack = vec![(0, 0)];
+        while let Some((a_node, b_node)) = stack.pop() {
+            if self.edge_label(a_node) != other.edge_label(b_node) {
+                return false;
+            }
+
+            match (&self.nodes[a_node], &other.nodes[b_node]) {
+                (&Node::Leaf(LeafNode { seq_id: a_seq_id, .. }), &Node::Leaf(LeafNode { seq_id: b_seq_id, .. })) => {
+                    if a_seq_id != b_seq_id || self.string_depths[a_node] != other.string_depths[b_node] {
+                        return false;
+                    }
+                },
+                (&Node::Leaf(_), _) | (_, &Node::Leaf(_)) => return false,
+                _ => {
+                    let mut a_children = self.children(a_node);
+                    let mut b_children = other.children(b_node);
+                    loop {
+                        match (a_children.next(), b_children.next()) {
+                            (Some(a_child), Some(b_child)) => stack.push((a_child, b_child)),
+                            (None, None) => break,
+                            _ => return false,
+                        }
+                    }
+                },
+            }
+        }
+
+        true
+    }
 }
 
 impl<'a, 'b> SuffixTree<'a, 'b> {
     fn new(maybe_alphabet: Option<Alphabet<'b>>) -> SuffixTree<'a, 'b> {
-        let alphabet = maybe_alphabet.unwrap_or_else(|| alphabet::ASCII.clone());
+        let alphabet = maybe_alphabet.unwrap_or_else(alphabet::default_alphabet);
         let alphabet_size = alphabet.size;
 
         SuffixTree {
             alphabet,
             sequences: Vec::new(),
             nodes: vec![Node::new_root(alphabet_size)],
+            suffix_links: vec![None],
+            string_depths: Vec::new(),
+            leaf_counts: Vec::new(),
+            sequence_id_sets: Vec::new(),
+            max_depth: None,
+            reversed: false,
+            circular: false,
         }
     }
 
@@ -205,6 +804,27 @@ impl<'a, 'b> SuffixTree<'a, 'b> {
         tree_builder.build()
     }
 
+    /// Adds the sequence to a new builder and returns it without building, so
+    /// that the tree can be inspected before `build` prepares it.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let builder = SuffixTree::builder_from_sequence(b"banana", None);
+    /// assert_eq!(builder.tree().children(0).count(), 4);
+    ///
+    /// let tree = builder.build();
+    /// assert_eq!(tree.find(b"ana").count(), 2);
+    /// ```
+    pub fn builder_from_sequence(sequence: &'a [u8], alphabet: Option<Alphabet<'b>>)
+        -> SuffixTreeBuilder<'a, 'b>
+    {
+        let mut tree_builder = SuffixTreeBuilder::new(alphabet);
+        tree_builder.add_sequence(sequence);
+        tree_builder
+    }
+
     pub fn from_sequences(sequences: &'a[&'a [u8]], alphabet: Option<Alphabet<'b>>)
         -> SuffixTree<'a, 'b>
     {
@@ -215,91 +835,466 @@ impl<'a, 'b> SuffixTree<'a, 'b> {
         tree_builder.build()
     }
 
-    pub fn pretty_print(&self) -> String {
-        fn format_line(
-            line: &str,
-            text: &str,
-            is_first_line: bool,
-            is_branch: bool,
-            is_last_child: bool
-        ) -> String {
-            let indent = " ".repeat(text.len());
-            match (is_first_line, is_branch, is_last_child) {
-                (true, _, _)          => format!("{}┳{}", text, line),
-                (false, true, false)  => format!("{}┣{}", indent, line),
-                (false, false, false) => format!("{}┃{}", indent, line),
-                (false, true, true)   => format!("{}┗{}", indent, line),
-                (false, false, true)  => format!("{} {}", indent, line),
-            }
-        }
-
-        /// Returns a formatted representation of the suffix tree.
-        fn pretty_print_node<'a, 'b>(tree: &SuffixTree<'a, 'b>, node: NodeId) -> Vec<String> {
-            let text = match tree.nodes[node] {
-                Node::Root(_) => {
-                    "".to_owned()
-                },
-                Node::Internal(InternalNode { seq_id, start, end, .. }) => {
-                    tree.sequences[seq_id].substring(start, Some(end))
-                },
-                Node::Leaf(LeafNode { seq_id, start, .. }) => {
-                    tree.sequences[seq_id].substring(start, None)
-                },
-            };
-
-            if tree.nodes[node].is_leaf() {
-                return vec![text];
-            }
-
-            let child_map = tree.nodes[node].children().unwrap();
-            let mut children: Vec<NodeId> = child_map.iter().collect();
-            children.sort();
-
-            let mut lines = Vec::new();
-            for (i, &child) in children.iter().enumerate() {
-                for (j, line) in pretty_print_node(tree, child).into_iter().enumerate() {
-                    let is_first_line = i == 0 && j == 0;
-                    let is_branch = j == 0;
-                    let is_last_child = i == children.len() - 1;
-
-                    lines.push(format_line(&line, &text, is_first_line, is_branch, is_last_child));
-                }
+    /// Builds a suffix tree over every window of length `k` of the sequence,
+    /// each added as a sequence of its own. The id of a window is therefore its
+    /// start position, and patterns longer than `k` are never found.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_kmers(b"abcabcd", 3, None);
+    /// assert_eq!(tree.sequence_count(), 5);
+    ///
+    /// let mut positions: Vec<usize> = tree.find(b"abc").map(|(seq_id, _, _)| seq_id).collect();
+    /// positions.sort();
+    /// assert_eq!(positions, vec![0, 3]);
+    /// assert!(!tree.contains(b"abca"));
+    /// ```
+    pub fn from_kmers(sequence: &'a [u8], k: usize, alphabet: Option<Alphabet<'b>>)
+        -> SuffixTree<'a, 'b>
+    {
+        let mut tree_builder = SuffixTreeBuilder::new(alphabet);
+        if k > 0 {
+            for kmer in sequence.windows(k) {
+                tree_builder.add_sequence(kmer);
             }
-
-            lines
         }
+        tree_builder.build()
+    }
 
-        pretty_print_node(&self, 0).join("\n")
+    /// Builds a suffix tree over the UTF-8 encoding of the given text. The
+    /// alphabet has to contain every byte of that encoding, which for non-ASCII
+    /// text rules out the default alphabet.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    /// use suffix_tree::alphabet::Alphabet;
+    ///
+    /// let alphabet = Alphabet::new("näe".as_bytes());
+    /// let tree = SuffixTree::from_str("nänä", Some(alphabet));
+    /// assert!(tree.contains("än".as_bytes()));
+    /// ```
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(text: &'a str, alphabet: Option<Alphabet<'b>>) -> SuffixTree<'a, 'b> {
+        SuffixTree::from_sequence(text.as_bytes(), alphabet)
     }
 
-    pub fn sequence_by_id(&self, seq_id: SequenceId) -> &'a [u8] {
-        self.sequences[seq_id].data
+    /// Turns the suffix tree back into a builder, so that further sequences can
+    /// be added. Each added sequence starts at the root, so no construction
+    /// state has to be restored apart from the tree itself.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"test", None);
+    /// let mut builder = tree.resume();
+    /// builder.add_sequence(b"rest");
+    /// let tree = builder.build();
+    ///
+    /// assert_eq!(tree.sequences_containing(b"est"), vec![0, 1]);
+    /// ```
+    pub fn resume(self) -> SuffixTreeBuilder<'a, 'b> {
+        assert!(self.max_depth.is_none(), "A truncated suffix tree cannot be extended.");
+        SuffixTreeBuilder::from_tree(self)
     }
 
-    fn add_sequence(&mut self, data: &'a [u8]) {
-        let seq_id = self.sequences.len();
-        assert!(seq_id < 128, "this suffix tree contains more than 128 sequences");
+    /// Returns a tree over the sequences of both trees, built with the alphabet
+    /// of `self`. Sequences of `self` keep their ids, while the sequence with id
+    /// `i` in `other` gets the id `self.sequence_count() + i`.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let a = SuffixTree::from_sequences(&[b"test", b"rest"], None);
+    /// let b = SuffixTree::from_sequence(b"nest", None);
+    /// let offset = a.sequence_count();
+    ///
+    /// let tree = a.merge(b);
+    /// assert_eq!(tree.sequence_by_id(offset), b"nest");
+    /// assert_eq!(tree.sequences_containing(b"est"), vec![0, 1, 2]);
+    /// ```
+    pub fn merge(self, other: SuffixTree<'a, 'b>) -> SuffixTree<'a, 'b> {
+        assert_eq!(self.reversed, other.reversed, "Only trees of the same orientation can be merged.");
+        assert_eq!(self.circular, other.circular, "Circular and linear trees cannot be merged.");
 
-        let sequence = Sequence::new(seq_id, data);
-        self.sequences.push(sequence);
-    }
+        let mut builder = self.resume();
+        for sequence in &other.sequences {
+            builder.add_sequence_of(sequence);
+        }
 
-    fn current_sequence(&self) -> Sequence {
-        self.sequences[self.sequences.len() - 1]
+        builder.build()
     }
 
-    fn add_node(&mut self, node: Node) -> NodeId {
-        let node_id = self.nodes.len();
-        self.nodes.push(node);
+    /// Returns a tree over only the sequences for which `keep` returns true,
+    /// built with the same alphabet and settings. The kept sequences are
+    /// renumbered in their original order, so ids start at 0 without gaps,
+    /// and occurences and `sequence_by_id` refer to the new ids.
+    ///
+    /// Suffix trees do not support removing a sequence, so the tree is built
+    /// again from the kept sequences.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"test", b"rest", b"nest"], None);
+    /// let tree = tree.filter_sequences(|seq_id| seq_id != 1);
+    ///
+    /// assert_eq!(tree.sequence_count(), 2);
+    /// assert_eq!(tree.sequence_by_id(1), b"nest");
+    /// assert_eq!(tree.sequences_containing(b"est"), vec![0, 1]);
+    /// assert!(!tree.contains(b"re"));
+    /// ```
+    pub fn filter_sequences<F>(self, mut keep: F) -> SuffixTree<'a, 'b>
+    where
+        F: FnMut(SequenceId) -> bool
+    {
+        // The root always stores its children densely, so the setting has to
+        // be taken from an internal node.
+        let child_storage = self.nodes.iter().find_map(|node| match *node {
+            Node::Internal(InternalNode { ref children, .. }) => Some(children.storage()),
+            _ => None,
+        }).unwrap_or_default();
+
+        let mut builder = SuffixTreeBuilder::new(Some(self.alphabet.clone()))
+            .with_child_storage(child_storage)
+            .with_reversed(self.reversed)
+            .with_circular(self.circular);
+        if let Some(max_depth) = self.max_depth {
+            builder = builder.with_max_depth(max_depth);
+        }
 
-        node_id
-    }
+        for sequence in &self.sequences {
+            if keep(sequence.id) {
+                builder.add_sequence_of(sequence);
+            }
+        }
 
-    fn add_child(&mut self, parent: NodeId, symbol: Symbol, child: NodeId) {
-        self.nodes[parent].add_child(&self.alphabet, symbol, child);
+        builder.build()
     }
 
-    fn get_child(&self, parent: NodeId, symbol: Symbol) -> Option<NodeId> {
+    /// Returns the children of the given node in lexicographic order of their
+    /// edge labels. The end of a sequence sorts before every symbol, and
+    /// symbols sort by their rank in the alphabet.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"aba", None);
+    /// let labels: Vec<&[u8]> = tree.children(0).map(|child| tree.edge_label(child)).collect();
+    /// assert_eq!(labels, vec![&b""[..], b"a", b"ba"]);
+    /// ```
+    pub fn children<'s>(&'s self, node: NodeId) -> Box<dyn Iterator<Item = NodeId> + 's> {
+        match self.nodes[node].children() {
+            Some(children) => children.iter(),
+            None => Box::new(iter::empty()),
+        }
+    }
+
+    /// Returns the symbols that start an edge below the root, ordered by their
+    /// rank in the alphabet. These are the symbols that actually occur in the
+    /// sequences, so comparing them to the alphabet shows which of its symbols
+    /// are unused. Edges that only hold the end of a sequence are left out.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    /// use suffix_tree::alphabet::Alphabet;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"abba", Some(Alphabet::new(b"abc")));
+    /// assert_eq!(tree.root_branches(), vec![b'a', b'b']);
+    /// ```
+    pub fn root_branches(&self) -> Vec<u8> {
+        self.root_node().children.regular_ranks().into_iter().map(|rank| {
+            self.alphabet.symbol_of_rank(rank)
+        }).collect()
+    }
+
+    /// Returns the parent of the given node, or `None` for the root.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"aba", None);
+    /// let node = tree.walk(b"ab").unwrap().node();
+    /// assert_eq!(tree.edge_label(node), b"ba");
+    ///
+    /// let parent = tree.parent(node).unwrap();
+    /// assert_eq!(tree.edge_label(parent), b"a");
+    /// assert_eq!(tree.parent(parent), Some(0));
+    /// assert_eq!(tree.parent(0), None);
+    /// ```
+    pub fn parent(&self, node: NodeId) -> Option<NodeId> {
+        self.nodes[node].parent()
+    }
+
+    /// Returns the ids of all sequences with a suffix in the subtree below the
+    /// given node, in ascending order.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"test", b"rest", b"toast"], None);
+    ///
+    /// let node = tree.walk(b"es").unwrap().node();
+    /// assert_eq!(tree.sequences_under(node).collect::<Vec<_>>(), vec![0, 1]);
+    /// assert_eq!(tree.sequences_under(0).count(), 3);
+    /// ```
+    pub fn sequences_under<'s>(&'s self, node: NodeId) -> impl Iterator<Item = SequenceId> + 's {
+        self.sequence_id_sets[node].iter()
+    }
+
+    /// Returns the number of sequences in the suffix tree.
+    pub fn sequence_count(&self) -> usize {
+        self.sequences.len()
+    }
+
+    /// Returns the length of the longest pattern the tree can find, if it was
+    /// built with `SuffixTreeBuilder::with_max_depth`.
+    pub fn max_depth(&self) -> Option<usize> {
+        self.max_depth
+    }
+
+    /// Drops the suffix links, which are only needed while sequences are
+    /// added. This saves 16 bytes per node for trees that are kept around for
+    /// queries. A shrunk tree can still be resumed, but adding sequences to it
+    /// no longer takes linear time.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let mut tree = SuffixTree::from_sequence(b"mississippi", None);
+    /// tree.shrink();
+    /// assert_eq!(tree.find(b"issi").count(), 2);
+    ///
+    /// let mut builder = tree.resume();
+    /// builder.add_sequence(b"missouri");
+    /// assert_eq!(builder.build().sequences_containing(b"iss"), vec![0, 1]);
+    /// ```
+    pub fn shrink(&mut self) {
+        self.suffix_links = Vec::new();
+    }
+
+    /// Releases the memory that the vectors of the tree reserved for growth
+    /// during construction, including the child lists of every node. Unlike
+    /// `shrink`, this keeps the suffix links. Resuming the tree afterwards
+    /// works as usual, but has to grow the vectors again.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let mut tree = SuffixTree::from_sequence(b"mississippi", None);
+    /// let before = tree.capacity_bytes();
+    /// tree.shrink_to_fit();
+    ///
+    /// assert!(tree.capacity_bytes() < before);
+    /// assert_eq!(tree.find(b"issi").count(), 2);
+    /// ```
+    pub fn shrink_to_fit(&mut self) {
+        self.sequences.shrink_to_fit();
+        self.nodes.shrink_to_fit();
+        for node in &mut self.nodes {
+            if let Some(children) = node.children_mut() {
+                children.shrink_to_fit();
+            }
+        }
+
+        self.suffix_links.shrink_to_fit();
+        self.string_depths.shrink_to_fit();
+        self.leaf_counts.shrink_to_fit();
+        self.sequence_id_sets.shrink_to_fit();
+        for id_set in &mut self.sequence_id_sets {
+            id_set.shrink_to_fit();
+        }
+    }
+
+    /// Returns the number of bytes the tree has allocated on the heap,
+    /// including unused capacity. The sequences are borrowed and not counted,
+    /// neither is the alphabet, which is usually shared.
+    pub fn capacity_bytes(&self) -> usize {
+        let nodes = self.nodes.capacity() * mem::size_of::<Node>()
+            + self.nodes.iter().filter_map(Node::children).map(ChildMap::heap_bytes).sum::<usize>();
+        let id_sets = self.sequence_id_sets.capacity() * mem::size_of::<SequenceIdSet>()
+            + self.sequence_id_sets.iter().map(SequenceIdSet::heap_bytes).sum::<usize>();
+
+        self.sequences.capacity() * mem::size_of::<Sequence>()
+            + nodes
+            + self.suffix_links.capacity() * mem::size_of::<Option<NodeId>>()
+            + self.string_depths.capacity() * mem::size_of::<usize>()
+            + self.leaf_counts.capacity() * mem::size_of::<usize>()
+            + id_sets
+    }
+
+    pub fn pretty_print(&self) -> String {
+        self.pretty_print_with(PrettyPrintOptions::default())
+    }
+
+    /// Returns a formatted representation of the suffix tree, rendered
+    /// according to the given options.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::{PrettyPrintOptions, SuffixTree};
+    ///
+    /// let tree = SuffixTree::from_sequence(b"abcdef", None);
+    /// let options = PrettyPrintOptions { max_edge_len: Some(2), ..Default::default() };
+    /// assert!(tree.pretty_print_with(options).contains("┣ab…"));
+    /// ```
+    pub fn pretty_print_with(&self, options: PrettyPrintOptions) -> String {
+        fn format_label(label: String, options: &PrettyPrintOptions) -> String {
+            match options.max_edge_len {
+                Some(max_len) if label.chars().count() > max_len => {
+                    let truncated: String = label.chars().take(max_len).collect();
+                    format!("{}…", truncated)
+                },
+                _ => label,
+            }
+        }
+
+        // Every leaf is printed on a line of its own, prefixed by one segment
+        // for each node above it. The first line below a node starts with the
+        // text of that node, all others with the continuation of the branches
+        // above, which is shared by all nodes on the path and truncated when
+        // the walk returns to a sibling. `head` holds the prefix of the next
+        // line, as the next leaf is always below the node visited last.
+        let mut lines = Vec::new();
+        let mut head = String::new();
+        let mut continuation = String::new();
+        // The node, its parent's depth and the length of the parent's
+        // continuation, the width of the parent's text, and whether the node
+        // is its parent's first and last child.
+        let mut stack = vec![(0, 0, 0, 0, true, true)];
+        while let Some((node, depth, continuation_len, indent, is_first, is_last)) = stack.pop() {
+            continuation.truncate(continuation_len);
+            if node != 0 {
+                if !is_first {
+                    head.clear();
+                    head.push_str(&continuation);
+                    head.extend(iter::repeat_n(' ', indent));
+                    head.push(if is_last { '┗' } else { '┣' });
+                }
+
+                continuation.extend(iter::repeat_n(' ', indent));
+                continuation.push(if is_last { ' ' } else { '┃' });
+            }
+
+            let (mut text, edge_length) = match self.nodes[node] {
+                Node::Root(_) => {
+                    ("".to_owned(), 0)
+                },
+                Node::Internal(InternalNode { seq_id, start, end, .. }) => {
+                    let label = self.sequences[seq_id].substring(start, Some(end));
+                    (format_label(label, &options), end - start)
+                },
+                Node::Leaf(LeafNode { seq_id, start, .. }) => {
+                    let label = self.sequences[seq_id].substring(start, None);
+                    (format_label(label, &options), self.sequences[seq_id].len() - start)
+                },
+            };
+            let depth = depth + edge_length;
+
+            if node != 0 {
+                if options.show_node_ids {
+                    text.push_str(&format!(" ({}, depth {})", node, depth));
+                }
+
+                if options.show_suffix_links {
+                    if let Some(link) = self.suffix_link(node) {
+                        text.push_str(&format!(" -> {}", link));
+                    }
+                }
+            }
+
+            if self.nodes[node].is_leaf() {
+                lines.push(format!("{}{}", head, text));
+                continue;
+            }
+
+            head.push_str(&text);
+            head.push('┳');
+
+            let children: Vec<NodeId> = self.children(node).collect();
+            let width = text.chars().count();
+            for (i, &child) in children.iter().enumerate().rev() {
+                stack.push((child, depth, continuation.len(), width, i == 0, i == children.len() - 1));
+            }
+        }
+
+        lines.join("\n")
+    }
+
+    /// Returns the sequence with the given id in the orientation in which
+    /// positions are reported, which for reversed trees is the original one.
+    pub fn sequence_by_id(&self, seq_id: SequenceId) -> &'a [u8] {
+        self.sequences[seq_id].original
+    }
+
+    /// Returns how often every symbol occurs in the given sequence.
+    pub fn symbol_frequencies(&self, seq_id: SequenceId) -> HashMap<u8, usize> {
+        let mut frequencies = HashMap::new();
+        for &symbol in self.sequences[seq_id].data {
+            *frequencies.entry(symbol).or_insert(0) += 1;
+        }
+
+        frequencies
+    }
+
+    /// Returns the Shannon entropy of the given sequence in bits per symbol,
+    /// which is 0 for an empty sequence.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"abab", b"aaaa", b""], None);
+    ///
+    /// assert_eq!(tree.shannon_entropy(0), 1.0);
+    /// assert_eq!(tree.shannon_entropy(1), 0.0);
+    /// assert_eq!(tree.shannon_entropy(2), 0.0);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn shannon_entropy(&self, seq_id: SequenceId) -> f64 {
+        let length = self.sequences[seq_id].data.len() as f64;
+
+        self.symbol_frequencies(seq_id).values().map(|&count| {
+            let probability = count as f64 / length;
+            -probability * probability.log2()
+        }).sum()
+    }
+
+    fn add_sequence(&mut self, data: &'a [u8], original: &'a [u8]) {
+        let seq_id = self.sequences.len();
+        let sequence = Sequence::new(seq_id, data, original);
+        self.sequences.push(sequence);
+    }
+
+    fn current_sequence(&self) -> Sequence<'a> {
+        self.sequences[self.sequences.len() - 1]
+    }
+
+    fn add_node(&mut self, node: Node) -> NodeId {
+        let node_id = self.nodes.len();
+        self.nodes.push(node);
+        self.suffix_links.push(None);
+
+        node_id
+    }
+
+    fn add_child(&mut self, parent: NodeId, symbol: Symbol, child: NodeId) {
+        self.nodes[parent].add_child(&self.alphabet, symbol, child);
+        self.nodes[child].set_parent(parent);
+    }
+
+    fn get_child(&self, parent: NodeId, symbol: Symbol) -> Option<NodeId> {
         self.nodes[parent].get_child(&self.alphabet, symbol)
     }
 
@@ -311,192 +1306,1762 @@ impl<'a, 'b> SuffixTree<'a, 'b> {
         }
     }
 
-    fn internal_node(&self, node_id: NodeId) -> Option<&InternalNode> {
-        if let Node::Internal(ref node) = self.nodes[node_id] {
-            Some(node)
-        } else {
-            None
+    fn suffix_link(&self, node: NodeId) -> Option<NodeId> {
+        self.suffix_links.get(node).cloned().flatten()
+    }
+
+
+    /// Returns all nodes ordered so that every node comes before its children.
+    /// Traversals that would otherwise recurse walk this list forwards or
+    /// backwards instead, as the tree can be as deep as its longest sequence.
+    fn preorder(&self) -> Vec<NodeId> {
+        let mut order = Vec::with_capacity(self.nodes.len());
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            order.push(node);
+            if let Some(children) = self.nodes[node].children() {
+                let _ = children.try_for_each(|child| {
+                    stack.push(child);
+                    ControlFlow::<()>::Continue(())
+                });
+            }
+        }
+
+        order
+    }
+
+    fn prepare_node_statistics(&mut self) {
+        let order = self.preorder();
+
+        let mut string_depths = vec![0; self.nodes.len()];
+        for &node in &order {
+            if let Some(children) = self.nodes[node].children() {
+                for child in children.iter() {
+                    let edge_length = match self.nodes[child] {
+                        Node::Root(_) => 0,
+                        Node::Internal(InternalNode { start, end, .. }) => end - start,
+                        Node::Leaf(LeafNode { seq_id, start, .. }) => {
+                            self.sequences[seq_id].len() - start
+                        },
+                    };
+                    string_depths[child] = string_depths[node] + edge_length;
+                }
+            }
+        }
+
+        let mut leaf_counts = vec![0; self.nodes.len()];
+        for &node in order.iter().rev() {
+            leaf_counts[node] = match self.nodes[node].children() {
+                Some(children) => children.iter().map(|child| leaf_counts[child]).sum(),
+                None => 1,
+            };
+        }
+
+        self.string_depths = string_depths;
+        self.leaf_counts = leaf_counts;
+    }
+
+    /// Returns the length of the path from the root to the given node, counting
+    /// the terminal symbol at the end of a leaf.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"abab", None);
+    /// let cursor = tree.walk(b"a").unwrap();
+    /// assert_eq!(tree.string_depth(cursor.node()), 2);
+    /// ```
+    pub fn string_depth(&self, node: NodeId) -> usize {
+        self.string_depths[node]
+    }
+
+    /// Returns the sequence id and the suffix spelled out by the path from the
+    /// root to the given leaf, without the terminal symbol. The leaf itself
+    /// only knows where its incoming edge starts, so the start of the suffix is
+    /// derived from the string depth.
+    ///
+    /// Panics if the node is not a leaf.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"abab", None);
+    /// let leaf = tree.walk(b"bab").unwrap().node();
+    /// assert_eq!(tree.edge_label(leaf), b"ab");
+    /// assert_eq!(tree.leaf_suffix(leaf), (0, &b"bab"[..]));
+    /// ```
+    pub fn leaf_suffix(&self, leaf: NodeId) -> (SequenceId, &'a [u8]) {
+        match self.nodes[leaf] {
+            Node::Leaf(LeafNode { seq_id, .. }) => {
+                let sequence = &self.sequences[seq_id];
+                (seq_id, &sequence.data[sequence.len() - self.string_depths[leaf]..])
+            },
+            _ => panic!("Node {} is not a leaf.", leaf),
+        }
+    }
+
+    fn prepare_lcs(&mut self) {
+        // Children come after their parent in the preorder, so walking it
+        // backwards finishes the sets of all children before their parent.
+        let mut sequence_id_sets = vec![SequenceIdSet::new(); self.nodes.len()];
+        for node in self.preorder().into_iter().rev() {
+            sequence_id_sets[node] = match self.nodes[node] {
+                Node::Root(RootNode { ref children }) |
+                Node::Internal(InternalNode { ref children, .. }) => {
+                    let mut id_set = SequenceIdSet::new();
+                    for child in children.iter() {
+                        id_set.union_with(&sequence_id_sets[child]);
+                    }
+
+                    id_set
+                },
+                Node::Leaf(LeafNode { seq_id, .. }) => SequenceIdSet::singleton(seq_id),
+            };
+        }
+
+        self.sequence_id_sets = sequence_id_sets;
+    }
+
+    /// Returns all occurences of the longest common subsequence in suffix tree.
+    /// If there are multiple such subsequences it just returns the occurences
+    /// of a random one.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let mut tree = SuffixTree::from_sequences(&[b"test", b"rest", b"estland"], None);
+    /// let mut occurences = tree.longest_common_subsequence();
+    /// for (seq_id, start, end) in occurences {
+    ///     assert_eq!(&tree.sequence_by_id(seq_id)[start..end], b"est")
+    /// }
+    /// ```
+    pub fn longest_common_subsequence<'s>(&'s self) -> Occurences<'s> {
+        let all_ids = SequenceIdSet::full(self.sequences.len());
+        let maybe_node = self.deepest_node_containing(&all_ids);
+
+        if let Some((node, depth)) = maybe_node {
+            let edge_length = self.edge_len(node);
+            self.occurences_of_node(node, edge_length, depth)
+        } else {
+            Occurences::empty()
+        }
+    }
+
+    /// Like `longest_common_subsequence`, but only requires the substring to be
+    /// common to the sequences that are at least `min_seq_len` bytes long, so
+    /// that a few very short sequences do not reduce the result to nothing.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"testing", b"x", b"resting"], None);
+    /// assert_eq!(tree.longest_common_subsequence().len(), 0);
+    ///
+    /// let occurences = tree.longest_common_substring_min_len(2);
+    /// assert_eq!(occurences.len(), 2);
+    /// for (seq_id, start, end) in occurences {
+    ///     assert_eq!(&tree.sequence_by_id(seq_id)[start..end], b"esting");
+    /// }
+    /// ```
+    pub fn longest_common_substring_min_len<'s>(&'s self, min_seq_len: usize) -> Occurences<'s> {
+        let id_set = self.sequences.iter()
+            .filter(|sequence| sequence.data.len() >= min_seq_len)
+            .fold(SequenceIdSet::new(), |mut id_set, sequence| {
+                id_set.insert(sequence.id);
+                id_set
+            });
+
+        if id_set.is_empty() {
+            return Occurences::empty();
+        }
+
+        if let Some((node, depth)) = self.deepest_node_containing(&id_set) {
+            let edge_length = self.edge_len(node);
+            self.occurences_of_node(node, edge_length, depth)
+        } else {
+            Occurences::empty()
+        }
+    }
+
+    /// Returns one occurence of every distinct longest common substring. Unlike
+    /// `longest_common_subsequence`, ties are not resolved arbitrarily.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"abxcd", b"cdyab"], None);
+    /// let mut substrings: Vec<&[u8]> = tree.all_longest_common_substrings().into_iter()
+    ///     .map(|(seq_id, start, end)| &tree.sequence_by_id(seq_id)[start..end])
+    ///     .collect();
+    /// substrings.sort();
+    ///
+    /// assert_eq!(substrings, vec![b"ab", b"cd"]);
+    /// ```
+    pub fn all_longest_common_substrings(&self) -> Vec<(SequenceId, usize, usize)> {
+        let all_ids = SequenceIdSet::full(self.sequences.len());
+        let mut nodes = Vec::new();
+        let mut stack = vec![(0, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            let (children, depth) = match self.nodes[node] {
+                Node::Root(RootNode { ref children }) => (children, depth),
+                Node::Internal(InternalNode { start, end, ref children, .. }) => {
+                    if !self.sequence_id_sets[node].is_superset(&all_ids) {
+                        continue;
+                    }
+
+                    let depth = depth + end - start;
+                    nodes.push((node, depth));
+                    (children, depth)
+                },
+                Node::Leaf(_) => continue,
+            };
+
+            // Children are pushed in reverse so they are visited in order.
+            let first = stack.len();
+            stack.extend(children.iter().map(|child| (child, depth)));
+            stack[first..].reverse();
+        }
+
+        let max_depth = nodes.iter().map(|&(_, depth)| depth).max().unwrap_or(0);
+        nodes.into_iter().filter(|&(_, depth)| depth == max_depth).map(|(node, depth)| {
+            let edge_length = self.edge_len(node);
+
+            let (seq_id, position) = self.node_occurences(node, 0).next().unwrap();
+            let end = position + edge_length;
+            self.original_range(seq_id, match_start(seq_id, end, depth), end)
+        }).collect()
+    }
+
+    /// Returns an occurence of each substring common to all sequences that
+    /// ends at an internal node, as its length, sequence id, start and end,
+    /// from the longest to the shortest. Substrings of the same length come
+    /// in no particular order. The first item is a longest common substring,
+    /// so `take(n)` gives the `n` longest candidates. Common substrings ending
+    /// within an edge are prefixes of the one ending at the node below it and
+    /// are not listed separately.
+    ///
+    /// The qualifying nodes are collected into a heap upfront, the occurences
+    /// are only looked up for the items that are actually taken.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"xabcyde", b"deabcz"], None);
+    /// let mut substrings = tree.common_substrings_by_length();
+    ///
+    /// let (length, seq_id, start, end) = substrings.next().unwrap();
+    /// assert_eq!(length, 3);
+    /// assert_eq!(&tree.sequence_by_id(seq_id)[start..end], b"abc");
+    ///
+    /// // "bc", "de", "c" and "e".
+    /// assert_eq!(substrings.map(|(length, ..)| length).collect::<Vec<_>>(), vec![2, 2, 1, 1]);
+    /// ```
+    pub fn common_substrings_by_length<'s>(&'s self)
+        -> impl Iterator<Item = (usize, SequenceId, usize, usize)> + 's
+    {
+        let all_ids = SequenceIdSet::full(self.sequences.len());
+        let mut heap: BinaryHeap<(usize, NodeId)> = (1..self.nodes.len()).filter(|&node| {
+            !self.nodes[node].is_leaf() && self.sequence_id_sets[node].is_superset(&all_ids)
+        }).map(|node| (self.string_depths[node], node)).collect();
+
+        iter::from_fn(move || {
+            let (depth, node) = heap.pop()?;
+            let edge_length = self.edge_len(node);
+            let (seq_id, position) = self.node_occurences(node, 0).next().unwrap();
+            let end = position + edge_length;
+            let (seq_id, start, end) = self.original_range(seq_id, match_start(seq_id, end, depth), end);
+            Some((depth, seq_id, start, end))
+        })
+    }
+
+    /// Returns an occurence of the substring common to at least two sequences
+    /// that maximizes its length times the total weight of the sequences it
+    /// occurs in, where `weights[seq_id]` is the weight of a sequence. With
+    /// all weights equal this prefers substrings that are both long and
+    /// widespread, unlike `longest_common_subsequence`, which requires the
+    /// substring to occur in every sequence. Returns `None` if no substring
+    /// is common to two sequences. Ties are resolved in favour of the
+    /// substring that comes first lexicographically.
+    ///
+    /// Panics if there is not exactly one weight per sequence.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"abcdxyz", b"abcdq", b"xyzq", b"xyzr"], None);
+    ///
+    /// let (seq_id, start, end) = tree.weighted_common_substring(&[1.0, 1.0, 1.0, 1.0]).unwrap();
+    /// assert_eq!(&tree.sequence_by_id(seq_id)[start..end], b"xyz");
+    ///
+    /// let (seq_id, start, end) = tree.weighted_common_substring(&[1.0, 5.0, 1.0, 1.0]).unwrap();
+    /// assert_eq!(&tree.sequence_by_id(seq_id)[start..end], b"abcd");
+    /// ```
+    pub fn weighted_common_substring(&self, weights: &[f64]) -> Option<(SequenceId, usize, usize)> {
+        assert_eq!(weights.len(), self.sequences.len(), "There has to be one weight per sequence.");
+
+        let mut best = None;
+        let mut stack = vec![(0, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            let (children, depth) = match self.nodes[node] {
+                Node::Root(RootNode { ref children }) => (children, depth),
+                Node::Internal(InternalNode { start, end, ref children, .. }) => {
+                    let id_set = &self.sequence_id_sets[node];
+                    if id_set.len() < 2 {
+                        continue;
+                    }
+
+                    let depth = depth + end - start;
+                    let score = depth as f64 * id_set.iter().map(|seq_id| weights[seq_id]).sum::<f64>();
+                    if best.is_none_or(|(_, _, best_score)| score > best_score) {
+                        best = Some((node, depth, score));
+                    }
+
+                    (children, depth)
+                },
+                Node::Leaf(_) => continue,
+            };
+
+            // Children are pushed in reverse so they are visited in order.
+            let first = stack.len();
+            stack.extend(children.iter().map(|child| (child, depth)));
+            stack[first..].reverse();
+        }
+
+        let (node, depth, _) = best?;
+        let edge_length = self.edge_len(node);
+        let (seq_id, position) = self.node_occurences(node, 0).next().unwrap();
+        let end = position + edge_length;
+        Some(self.original_range(seq_id, match_start(seq_id, end, depth), end))
+    }
+
+    /// Returns an occurence in sequence `a` of the longest substring that is
+    /// common to the sequences `a` and `b`.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"testing", b"festung", b"resting"], None);
+    /// let (seq_id, start, end) = tree.longest_common_substring_pair(0, 2).unwrap();
+    ///
+    /// assert_eq!(seq_id, 0);
+    /// assert_eq!(&tree.sequence_by_id(seq_id)[start..end], b"esting");
+    /// ```
+    pub fn longest_common_substring_pair(&self, a: SequenceId, b: SequenceId)
+        -> Option<(SequenceId, usize, usize)>
+    {
+        let mut id_set = SequenceIdSet::singleton(a);
+        id_set.insert(b);
+
+        let (node, depth) = self.deepest_node_containing(&id_set)?;
+        let edge_length = self.edge_len(node);
+
+        self.node_occurences(node, 0).find(|&(seq_id, _)| seq_id == a).map(|(_, position)| {
+            let end = position + edge_length;
+            self.original_range(a, match_start(a, end, depth), end)
+        })
+    }
+
+    /// Returns the length of the longest suffix of sequence `a` that is also a
+    /// prefix of sequence `b`, which is how far the end of `a` overlaps the
+    /// start of `b` when assembling them into one sequence.
+    ///
+    /// The prefixes of `b` are walked from the root. A suffix of `a` equals
+    /// the prefix walked so far whenever the walk reaches a node with a child
+    /// for the terminal of `a`, or the end of the label of a leaf of `a`. This
+    /// takes time linear in the length of `b`.
+    ///
+    /// Panics for trees built with a maximum depth.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"GATTAC", b"TACAGG"], None);
+    ///
+    /// assert_eq!(tree.overlap(0, 1), 3);
+    /// assert_eq!(tree.overlap(1, 0), 1);
+    /// ```
+    pub fn overlap(&self, a: SequenceId, b: SequenceId) -> usize {
+        assert!(self.max_depth.is_none(), "A truncated suffix tree cannot find overlaps.");
+
+        let mut overlap = 0;
+        let mut cursor = self.walk(&[]).unwrap();
+        for &byte in self.sequences[b].data {
+            let stepped = cursor.step(byte);
+            debug_assert!(stepped, "Prefixes of a sequence are always contained.");
+
+            let node = cursor.node();
+            if cursor.offset() < self.edge_len(node) {
+                continue;
+            }
+
+            let ends_suffix_of_a = match self.nodes[node] {
+                Node::Leaf(LeafNode { seq_id, .. }) => seq_id == a,
+                _ => self.get_child(node, Symbol::Terminal(a)).is_some(),
+            };
+            if ends_suffix_of_a {
+                overlap = cursor.depth();
+            }
+        }
+
+        overlap
+    }
+
+    /// Returns the start and end of an occurence of the longest substring that
+    /// occurs at least `k` times in the given sequence, where occurences may
+    /// overlap. With `k = 2` this is the longest repeated substring.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"abcabcab", None);
+    ///
+    /// let (start, end) = tree.longest_k_repeat(0, 2).unwrap();
+    /// assert_eq!(&tree.sequence_by_id(0)[start..end], b"abcab");
+    ///
+    /// let (start, end) = tree.longest_k_repeat(0, 3).unwrap();
+    /// assert_eq!(&tree.sequence_by_id(0)[start..end], b"ab");
+    /// ```
+    pub fn longest_k_repeat(&self, seq_id: SequenceId, k: usize) -> Option<(usize, usize)> {
+        if k <= 1 {
+            return Some((0, self.sequences[seq_id].data.len()));
+        }
+
+        // Walking the preorder backwards visits every node after its children,
+        // in the same order as a recursive traversal would finish them.
+        let mut counts = vec![0; self.nodes.len()];
+        let mut best = None;
+        for &node in self.preorder().iter().rev() {
+            match self.nodes[node] {
+                Node::Internal(InternalNode { ref children, .. }) => {
+                    let depth = self.string_depths[node];
+                    let count = children.iter().map(|child| counts[child]).sum();
+                    if count >= k && best.is_none_or(|(_, best_depth)| depth > best_depth) {
+                        best = Some((node, depth));
+                    }
+
+                    counts[node] = count;
+                },
+                Node::Leaf(LeafNode { seq_id: leaf_seq_id, .. }) => counts[node] = (leaf_seq_id == seq_id) as usize,
+                Node::Root(_) => {},
+            }
+        }
+
+        let (node, depth) = best?;
+        let edge_length = self.edge_len(node);
+        self.node_occurences(node, 0).find(|&(id, _)| id == seq_id).map(|(_, position)| {
+            let end = position + edge_length;
+            let (_, start, end) = self.original_range(seq_id, match_start(seq_id, end, depth), end);
+            (start, end)
+        })
+    }
+
+    /// Returns the start and end of an occurence of the longest substring that
+    /// is repeated in the target sequence but does not occur in any other
+    /// sequence, for finding motifs that set the target apart from the rest.
+    ///
+    /// These substrings end at the internal nodes whose subtrees only contain
+    /// suffixes of the target. Substrings occuring only once are not
+    /// considered, as the longest of those is always the whole target.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"xabcyabcz", b"abxbcy", b"yab"], None);
+    ///
+    /// // "abc" is repeated in the first sequence only, "ab" and "bc" also
+    /// // occur in the second.
+    /// let (start, end) = tree.longest_unique_substring(0).unwrap();
+    /// assert_eq!(&tree.sequence_by_id(0)[start..end], b"abc");
+    ///
+    /// assert_eq!(tree.longest_unique_substring(2), None);
+    /// ```
+    pub fn longest_unique_substring(&self, target: SequenceId) -> Option<(usize, usize)> {
+        let mut best = None;
+        let mut stack = vec![(0, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            let (children, depth) = match self.nodes[node] {
+                Node::Root(RootNode { ref children }) => (children, depth),
+                Node::Internal(InternalNode { start, end, ref children, .. }) => {
+                    let id_set = &self.sequence_id_sets[node];
+                    if !id_set.contains(target) {
+                        continue;
+                    }
+
+                    let depth = depth + end - start;
+                    if id_set.len() == 1 && best.is_none_or(|(_, best_depth)| depth > best_depth) {
+                        best = Some((node, depth));
+                    }
+
+                    (children, depth)
+                },
+                Node::Leaf(_) => continue,
+            };
+
+            // Children are pushed in reverse so they are visited in order.
+            let first = stack.len();
+            stack.extend(children.iter().map(|child| (child, depth)));
+            stack[first..].reverse();
+        }
+
+        let (node, depth) = best?;
+        let edge_length = self.edge_len(node);
+        let (seq_id, position) = self.node_occurences(node, 0).next().unwrap();
+        let end = position + edge_length;
+        let (_, start, end) = self.original_range(seq_id, match_start(seq_id, end, depth), end);
+        Some((start, end))
+    }
+
+    /// Returns the shortest string over the alphabet that does not occur in any
+    /// of the sequences. Among strings of the same length, the one that comes
+    /// first in the order of the alphabet is returned.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    /// use suffix_tree::alphabet::Alphabet;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"aabba", Some(Alphabet::new(b"ab")));
+    /// assert_eq!(tree.shortest_absent_word(), b"aaa");
+    /// ```
+    pub fn shortest_absent_word(&self) -> Vec<u8> {
+        let mut queue = VecDeque::new();
+        queue.push_back((self.walk(b"").unwrap(), Vec::new()));
+
+        while let Some((cursor, word)) = queue.pop_front() {
+            for rank in 0..self.alphabet.size {
+                let symbol = self.alphabet.symbol_of_rank(rank);
+                let mut next_cursor = cursor.clone();
+                let mut next_word = word.clone();
+                next_word.push(symbol);
+
+                if !next_cursor.step(symbol) {
+                    return next_word;
+                }
+
+                queue.push_back((next_cursor, next_word));
+            }
+        }
+
+        Vec::new()
+    }
+
+    /// Returns the deepest internal node whose subtree contains suffixes of
+    /// at least all the sequences in `id_set`, along with its string depth.
+    fn deepest_node_containing(&self, id_set: &SequenceIdSet) -> Option<(NodeId, usize)> {
+        let mut deepest = None;
+        let mut stack = vec![(0, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            let (children, depth) = match self.nodes[node] {
+                Node::Root(RootNode { ref children }) => (children, depth),
+                Node::Internal(InternalNode { start, end, ref children, .. }) => {
+                    if !self.sequence_id_sets[node].is_superset(id_set) {
+                        continue;
+                    }
+
+                    // Ties go to the node visited last, as nodes of equal depth
+                    // are never nested.
+                    let depth = depth + end - start;
+                    if deepest.is_none_or(|(_, deepest_depth)| depth >= deepest_depth) {
+                        deepest = Some((node, depth));
+                    }
+
+                    (children, depth)
+                },
+                Node::Leaf(_) => continue,
+            };
+
+            // Children are pushed in reverse so they are visited in order.
+            let first = stack.len();
+            let _ = children.try_for_each(|child| {
+                stack.push((child, depth));
+                ControlFlow::<()>::Continue(())
+            });
+            stack[first..].reverse();
+        }
+
+        deepest
+    }
+
+    /// Returns substrings of length at least `min_len` that occur in at least
+    /// `min_sequences` of the sequences. One representative occurrence is
+    /// returned per qualifying node, so shorter substrings that are prefixes
+    /// of a reported one are not listed separately.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"testing", b"resting", b"fasting"], None);
+    /// let substrings = tree.frequent_substrings(5, 3);
+    /// assert_eq!(substrings.len(), 1);
+    ///
+    /// let (seq_id, start, end) = substrings[0];
+    /// assert_eq!(&tree.sequence_by_id(seq_id)[start..end], b"sting");
+    /// ```
+    pub fn frequent_substrings(&self, min_len: usize, min_sequences: usize)
+        -> Vec<(SequenceId, usize, usize)>
+    {
+        let mut result = Vec::new();
+        let mut stack = vec![(0, 0)];
+        while let Some((node, depth)) = stack.pop() {
+            let (children, depth) = match self.nodes[node] {
+                Node::Root(RootNode { ref children }) => (children, depth),
+                Node::Internal(InternalNode { start, end, ref children, .. }) => {
+                    if self.sequence_id_sets[node].len() < min_sequences {
+                        continue;
+                    }
+
+                    let edge_length = end - start;
+                    let node_depth = depth + edge_length;
+                    if node_depth >= min_len {
+                        let (seq_id, position) = self.node_occurences(node, 0).next().unwrap();
+                        let end = position + edge_length;
+                        result.push(self.original_range(seq_id, match_start(seq_id, end, node_depth), end));
+                    }
+
+                    (children, node_depth)
+                },
+                Node::Leaf(_) => continue,
+            };
+
+            // Children are pushed in reverse so they are visited in order.
+            let first = stack.len();
+            stack.extend(children.iter().map(|child| (child, depth)));
+            stack[first..].reverse();
+        }
+
+        result
+    }
+
+    /// Returns the `k` most frequent substrings of exactly `length` bytes along
+    /// with their number of occurences, most frequent first. Substrings that
+    /// occur equally often are ordered lexicographically.
+    ///
+    /// Every substring of that length ends on the edge into the first node
+    /// whose string depth reaches `length`, so the number of its occurences is
+    /// the number of leaves below that node. Only the nodes above that depth
+    /// are visited.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"ACGTACGA", b"TACGT"], None);
+    /// let top = tree.top_k_frequent(3, 2);
+    ///
+    /// // "TAC" also occurs twice, but comes after "CGT".
+    /// assert_eq!(top, vec![(b"ACG".to_vec(), 3), (b"CGT".to_vec(), 2)]);
+    /// ```
+    pub fn top_k_frequent(&self, length: usize, k: usize) -> Vec<(Vec<u8>, usize)> {
+        if length == 0 || self.max_depth.is_some_and(|max_depth| length > max_depth) {
+            return Vec::new();
+        }
+
+        // Every child is pushed with the depth of its parent. Children are
+        // pushed in reverse so they are visited in order.
+        let mut result = Vec::new();
+        let mut stack: Vec<(NodeId, usize)> = self.children(0).map(|child| (child, 0)).collect();
+        stack.reverse();
+        while let Some((node, depth)) = stack.pop() {
+            let node_depth = depth + self.edge_len(node);
+            if node_depth >= length {
+                let (seq_id, position) = self.node_occurences(node, 0).next().unwrap();
+                let start = leaf_position(seq_id, position, depth);
+                let substring = self.sequences[seq_id].data[start..start + length].to_vec();
+                result.push((substring, self.leaf_counts[node]));
+            } else if !self.nodes[node].is_leaf() {
+                let first = stack.len();
+                stack.extend(self.children(node).map(|child| (child, node_depth)));
+                stack[first..].reverse();
+            }
+        }
+
+        // The substrings are found in lexicographic order, which the stable
+        // sort keeps among equally frequent ones.
+        result.sort_by_key(|&(_, count)| Reverse(count));
+        result.truncate(k);
+        result
+    }
+
+    /// Returns every distinct substring of at most `max_len` bytes together with
+    /// the number of its occurences. The map holds up to `max_len` entries per
+    /// suffix, so the cost grows quickly with `max_len` and long sequences.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"abab", None);
+    /// let substrings = tree.substrings_up_to(2);
+    ///
+    /// assert_eq!(substrings.len(), 4);
+    /// assert_eq!(substrings[&b"ab"[..]], 2);
+    /// assert_eq!(substrings[&b"ba"[..]], 1);
+    /// ```
+    pub fn substrings_up_to(&self, max_len: usize) -> HashMap<Vec<u8>, usize> {
+        fn _substrings_up_to<'a, 'b>(
+            tree: &SuffixTree<'a, 'b>,
+            node: NodeId,
+            path: &mut Vec<u8>,
+            max_len: usize,
+            result: &mut HashMap<Vec<u8>, usize>,
+        ) -> usize {
+            let label = tree.edge_label(node);
+            let parent_depth = path.len();
+            let take = label.len().min(max_len - parent_depth);
+            path.extend_from_slice(&label[..take]);
+
+            let count = match tree.nodes[node] {
+                Node::Internal(InternalNode { ref children, .. }) if path.len() < max_len => {
+                    children.iter().map(|child| {
+                        _substrings_up_to(tree, child, path, max_len, result)
+                    }).sum()
+                },
+                _ => tree.node_occurences(node, 0).count(),
+            };
+
+            for length in (parent_depth + 1)..=path.len() {
+                result.insert(path[..length].to_vec(), count);
+            }
+            path.truncate(parent_depth);
+
+            count
+        }
+
+        let mut result = HashMap::new();
+        if max_len == 0 {
+            return result;
+        }
+
+        let mut path = Vec::with_capacity(max_len);
+        for child in self.root_node().children.iter() {
+            _substrings_up_to(self, child, &mut path, max_len, &mut result);
+        }
+
+        result
+    }
+
+    /// Returns the Burrows-Wheeler transform of the given sequence. Suffixes are
+    /// sorted by the rank of their symbols in the alphabet, with the end of the
+    /// sequence sorting first, and the symbol preceding the suffix starting at
+    /// position 0 is written as `$`.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"banana", None);
+    /// assert_eq!(tree.bwt(0), b"annb$aa");
+    /// ```
+    pub fn bwt(&self, seq_id: SequenceId) -> Vec<u8> {
+        let data = self.sequences[seq_id].data;
+
+        self.root_node().children.iter().flat_map(|child| {
+            self.node_occurences(child, 0)
+        }).filter(|&(id, _)| id == seq_id).map(|(_, start)| {
+            if start == 0 { b'$' } else { data[start - 1] }
+        }).collect()
+    }
+
+    /// Returns the start positions of the suffixes of each sequence, indexed by
+    /// sequence id. Every suffix has a leaf, so the positions of a sequence are
+    /// `0..=len` in the order of their leaves, which sorts the suffixes like
+    /// `bwt` does. Each list is therefore the suffix array of its sequence,
+    /// including the empty suffix at the front.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"banana", b"band"], None);
+    /// let leaves = tree.leaves_by_sequence();
+    ///
+    /// assert_eq!(leaves[0], vec![6, 5, 3, 1, 0, 4, 2]);
+    /// assert_eq!(leaves[1], vec![4, 1, 0, 3, 2]);
+    /// ```
+    pub fn leaves_by_sequence(&self) -> Vec<Vec<usize>> {
+        let mut leaves = vec![Vec::new(); self.sequences.len()];
+        for child in self.root_node().children.iter() {
+            for (seq_id, start) in self.node_occurences(child, 0) {
+                leaves[seq_id].push(start);
+            }
+        }
+
+        leaves
+    }
+
+    /// Returns true when the given pattern is contained in the suffix tree. 
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"test", None);
+    ///
+    /// assert!(tree.contains(b"es"));
+    /// assert!(!tree.contains(b"asdf"));
+    /// ```
+    pub fn contains(&self, pattern: &[u8]) -> bool {
+        if self.max_depth.is_some_and(|max_depth| pattern.len() > max_depth) {
+            return false;
+        }
+
+        self.matches_path(pattern)
+    }
+
+    // Follows the path spelled out by the pattern like `find_node`, but only
+    // reports whether it exists. The first byte of every edge is already
+    // matched by looking up the child, so only the rest of it is compared.
+    fn matches_path(&self, pattern: &[u8]) -> bool {
+        let mut node = 0;
+        let mut depth = 0;
+        while depth < pattern.len() {
+            if self.nodes[node].is_leaf() {
+                return false;
+            }
+
+            node = match self.get_child(node, Symbol::Regular(pattern[depth])) {
+                Some(child) => child,
+                None => return false,
+            };
+
+            let label = self.edge_label(node);
+            let length = label.len().min(pattern.len() - depth);
+            if label[1..length] != pattern[depth + 1..depth + length] {
+                return false;
+            }
+
+            depth += length;
+        }
+
+        true
+    }
+
+    /// Returns the length of the longest prefix of the given pattern that is
+    /// contained in the suffix tree, i.e. how far the pattern matches before
+    /// it diverges from every substring.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"testing", None);
+    ///
+    /// assert_eq!(tree.longest_matching_prefix(b"tesla"), 3);
+    /// assert_eq!(tree.longest_matching_prefix(b"sting"), 5);
+    /// assert_eq!(tree.longest_matching_prefix(b"xyz"), 0);
+    /// assert_eq!(tree.longest_matching_prefix(b"te!"), 2);
+    /// ```
+    pub fn longest_matching_prefix(&self, pattern: &[u8]) -> usize {
+        let mut cursor = self.walk(&[]).unwrap();
+        for &byte in pattern {
+            if !cursor.step(byte) {
+                break;
+            }
+        }
+
+        cursor.depth()
+    }
+
+    /// Returns every byte that directly follows an occurence of the given
+    /// context, together with the number of occurences it follows, in the
+    /// order of the alphabet. Within an edge only one byte can follow, at a
+    /// node each child edge starts with a different one, and the number of
+    /// occurences is the number of leaves below it. For trees built with a
+    /// maximum depth, nothing is known to follow contexts of that length.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"thecat", b"thedog", b"then", b"other"], None);
+    ///
+    /// assert_eq!(tree.following_symbols(b"the"), vec![(b'c', 1), (b'd', 1), (b'n', 1), (b'r', 1)]);
+    /// assert_eq!(tree.following_symbols(b"th"), vec![(b'e', 4)]);
+    /// assert_eq!(tree.following_symbols(b"thec"), vec![(b'a', 1)]);
+    /// assert_eq!(tree.following_symbols(b"dog"), vec![]);
+    /// ```
+    pub fn following_symbols(&self, context: &[u8]) -> Vec<(u8, usize)> {
+        if self.max_depth.is_some_and(|max_depth| context.len() >= max_depth) {
+            return Vec::new();
+        }
+
+        let cursor = match self.walk(context) {
+            Some(cursor) => cursor,
+            None => return Vec::new(),
+        };
+
+        let node = cursor.node();
+        let label = self.edge_label(node);
+        if cursor.offset() < label.len() {
+            return vec![(label[cursor.offset()], self.leaf_counts[node])];
+        }
+
+        self.children(node).filter_map(|child| {
+            self.edge_first_char(child).map(|byte| (byte, self.leaf_counts[child]))
+        }).collect()
+    }
+
+    /// Returns a predicate telling whether a pattern is contained in the
+    /// suffix tree, for use with iterator adapters like `filter`.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"test", None);
+    /// let is_substring = tree.substring_set();
+    ///
+    /// let words: Vec<&[u8]> = vec![b"es", b"set", b"st"];
+    /// let substrings: Vec<&[u8]> = words.into_iter().filter(|word| is_substring(word)).collect();
+    /// assert_eq!(substrings, vec![b"es", b"st"]);
+    /// ```
+    pub fn substring_set<'s>(&'s self) -> impl Fn(&[u8]) -> bool + 's {
+        move |pattern| self.contains(pattern)
+    }
+
+    /// Returns all the occurences of the given pattern in the suffix tree. 
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"test", None);
+    /// let mut occurences = tree.find(b"es");
+    /// assert_eq!(occurences.next(), Some((0, 1, 3)));
+    /// assert_eq!(occurences.next(), None);
+    /// ```
+    pub fn find<'s>(&'s self, pattern: &[u8]) -> Occurences<'s> {
+        match self.walk(pattern) {
+            Some(cursor) => cursor.occurences(),
+            None => Occurences::empty(),
+        }
+    }
+
+    /// Returns the occurences of the given pattern in the circular sequences
+    /// of the tree, sorted by sequence id and start position. The start lies
+    /// within the circular sequence, while the end is past its length if the
+    /// occurence wraps around to the beginning.
+    ///
+    /// Every rotation occurs twice in the doubled sequence, once starting in
+    /// each copy, so only occurences starting in the first copy are reported.
+    /// Patterns longer than a circular sequence never occur in it.
+    ///
+    /// Panics if the tree was not built with `SuffixTreeBuilder::with_circular`.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let owned = SuffixTree::from_circular(b"GATTACA", None);
+    /// let tree = owned.tree();
+    ///
+    /// assert_eq!(tree.find_circular(b"CAG"), vec![(0, 5, 8)]);
+    /// assert_eq!(tree.find_circular(b"A"), vec![(0, 1, 2), (0, 4, 5), (0, 6, 7)]);
+    /// assert_eq!(tree.find_circular(b"AGATTACAG"), vec![]);
+    /// ```
+    pub fn find_circular(&self, pattern: &[u8]) -> Vec<(SequenceId, usize, usize)> {
+        assert!(self.circular, "The suffix tree was not built over circular sequences.");
+
+        let mut occurences: Vec<(SequenceId, usize, usize)> = self.find(pattern)
+            .filter(|&(seq_id, start, _)| {
+                let length = self.sequences[seq_id].data.len() / 2;
+                start < length && pattern.len() <= length
+            })
+            .collect();
+
+        occurences.sort_unstable();
+        occurences
+    }
+
+    /// Returns a single occurence of the given pattern, the same one `find`
+    /// would return first. Only the path from the end of the pattern down to
+    /// its first leaf is followed, the rest of the subtree is not visited.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"abcabc", b"xabc"], None);
+    ///
+    /// assert_eq!(tree.find_first(b"abc"), tree.find(b"abc").next());
+    /// assert_eq!(tree.find_first(b"xa"), Some((1, 0, 2)));
+    /// assert_eq!(tree.find_first(b"abx"), None);
+    /// ```
+    pub fn find_first(&self, pattern: &[u8]) -> Option<(SequenceId, usize, usize)> {
+        let cursor = self.walk(pattern)?;
+
+        let mut node = cursor.node();
+        let mut depth = 0;
+        loop {
+            match self.nodes[node] {
+                Node::Root(_) => return None,
+                Node::Internal(InternalNode { start, end, ref children, .. }) => {
+                    depth += end - start;
+                    node = children.first().unwrap();
+                },
+                Node::Leaf(LeafNode { seq_id, start, .. }) => {
+                    let end = leaf_position(seq_id, start, depth) + cursor.offset();
+                    return Some(self.original_range(seq_id, match_start(seq_id, end, pattern.len()), end));
+                },
+            }
+        }
+    }
+
+    /// Returns up to `limit` occurences of the given pattern, skipping the
+    /// first `offset` ones, for showing the occurences one page at a time.
+    ///
+    /// Paging only makes sense if every call sees the occurences in the same
+    /// order. `find` reports them in the lexicographic order of the suffixes
+    /// starting at them, with identical suffixes of different sequences
+    /// ordered by sequence id. This order only depends on the sequences, not
+    /// on how the tree was built, and is the order of the pages. Subtrees that
+    /// lie entirely before the page are skipped using their leaf counts, so
+    /// the cost does not grow with `offset`.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"abcab", b"cabx"], None);
+    /// let all: Vec<_> = tree.find(b"ab").collect();
+    /// assert_eq!(all, vec![(0, 3, 5), (0, 0, 2), (1, 1, 3)]);
+    ///
+    /// assert_eq!(tree.find_paged(b"ab", 0, 2), &all[..2]);
+    /// assert_eq!(tree.find_paged(b"ab", 2, 2), &all[2..]);
+    /// assert_eq!(tree.find_paged(b"ab", 4, 2), vec![]);
+    /// ```
+    pub fn find_paged(&self, pattern: &[u8], offset: usize, limit: usize)
+        -> Vec<(SequenceId, usize, usize)>
+    {
+        let cursor = match self.walk(pattern) {
+            Some(cursor) => cursor,
+            None => return Vec::new(),
+        };
+
+        let mut offset = offset;
+        let mut positions = Vec::new();
+        let mut stack = vec![(cursor.node(), 0)];
+        while positions.len() < limit {
+            let (node, depth) = match stack.pop() {
+                Some(entry) => entry,
+                None => break,
+            };
+
+            if offset >= self.leaf_counts[node] {
+                offset -= self.leaf_counts[node];
+                continue;
+            }
+
+            match self.nodes[node] {
+                Node::Root(_) => {},
+                Node::Internal(InternalNode { start, end, ref children, .. }) => {
+                    // Children are pushed in reverse so they are visited in order.
+                    let first = stack.len();
+                    stack.extend(children.iter().map(|child| (child, depth + end - start)));
+                    stack[first..].reverse();
+                },
+                Node::Leaf(LeafNode { seq_id, start, .. }) => {
+                    positions.push((seq_id, leaf_position(seq_id, start, depth)));
+                },
+            }
+        }
+
+        positions.into_iter().map(|(seq_id, position)| {
+            let end = position + cursor.offset();
+            self.original_range(seq_id, match_start(seq_id, end, pattern.len()), end)
+        }).collect()
+    }
+
+    /// Calls `f` with the sequence id, start and end of every occurence of the
+    /// given pattern while traversing the tree, without allocating an
+    /// iterator. The traversal stops as soon as `f` returns `ControlFlow::Break`,
+    /// whose value is then returned.
+    ///
+    /// #Examples
+    /// ```
+    /// use std::ops::ControlFlow;
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"abcabcabc", None);
+    ///
+    /// let mut count = 0;
+    /// tree.for_each_occurrence(b"bc", |_, _, _| {
+    ///     count += 1;
+    ///     ControlFlow::<()>::Continue(())
+    /// });
+    /// assert_eq!(count, 3);
+    ///
+    /// let first = tree.for_each_occurrence(b"bc", |seq_id, start, end| {
+    ///     ControlFlow::Break((seq_id, start, end))
+    /// });
+    /// assert!(first.is_break());
+    /// ```
+    pub fn for_each_occurrence<B, F>(&self, pattern: &[u8], mut f: F) -> ControlFlow<B>
+    where
+        F: FnMut(SequenceId, usize, usize) -> ControlFlow<B>
+    {
+        let cursor = match self.walk(pattern) {
+            Some(cursor) => cursor,
+            None => return ControlFlow::Continue(()),
+        };
+
+        let (offset, length) = (cursor.offset(), pattern.len());
+        for (seq_id, position) in SubtreeLeaves::new(self, cursor.node(), 0) {
+            let end = position + offset;
+            let (seq_id, start, end) = self.original_range(seq_id, match_start(seq_id, end, length), end);
+            f(seq_id, start, end)?;
+        }
+
+        ControlFlow::Continue(())
+    }
+
+    /// Returns the start and end of every occurence of the given pattern in
+    /// the sequence `seq_id` that starts within `lo..hi`, in ascending order.
+    /// Subtrees without suffixes of the sequence are skipped, but the
+    /// remaining occurences in the sequence are all visited and filtered by
+    /// position, so this is linear in their number.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"abababab", b"abab"], None);
+    ///
+    /// assert_eq!(tree.find_in_range(b"ab", 0, 1, 5), vec![(2, 4), (4, 6)]);
+    /// assert_eq!(tree.find_in_range(b"ab", 1, 0, 8), vec![(0, 2), (2, 4)]);
+    /// ```
+    pub fn find_in_range(&self, pattern: &[u8], seq_id: SequenceId, lo: usize, hi: usize)
+        -> Vec<(usize, usize)>
+    {
+        let cursor = match self.walk(pattern) {
+            Some(cursor) => cursor,
+            None => return Vec::new(),
+        };
+
+        // The positions are sorted below, so the children can be visited in
+        // any order.
+        let mut positions = Vec::new();
+        let mut stack = vec![(cursor.node(), 0)];
+        while let Some((node, depth)) = stack.pop() {
+            if !self.sequence_id_sets[node].contains(seq_id) {
+                continue;
+            }
+
+            match self.nodes[node] {
+                Node::Root(_) => {},
+                Node::Internal(InternalNode { start, end, ref children, .. }) => {
+                    stack.extend(children.iter().map(|child| (child, depth + end - start)));
+                },
+                Node::Leaf(LeafNode { seq_id, start, .. }) => {
+                    positions.push(leaf_position(seq_id, start, depth));
+                },
+            }
+        }
+
+        let (offset, length) = (cursor.offset(), pattern.len());
+        let mut occurences: Vec<(usize, usize)> = positions.into_iter().map(|position| {
+            let end = position + offset;
+            let (_, start, end) = self.original_range(seq_id, match_start(seq_id, end, length), end);
+            (start, end)
+        }).filter(|&(start, _)| lo <= start && start < hi).collect();
+        occurences.sort();
+
+        occurences
+    }
+
+    /// Returns occurences of the given pattern with at most `max_edits`
+    /// substitutions, insertions or deletions. For every start position the
+    /// end with the fewest edits is reported, preferring the shorter match on
+    /// ties. The edit distance table is extended along the edges of the tree,
+    /// so common prefixes of suffixes are only processed once.
+    ///
+    /// Matches are never empty. A pattern of at most `max_edits` bytes could
+    /// be deleted entirely at any position, including the end of a sequence,
+    /// but is instead reported at every start that has a non-empty match
+    /// within the limit, and an empty pattern is never reported, like with
+    /// `find`.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"duckquack", None);
+    /// let occurences = tree.find_within_edit_distance(b"quick", 1);
+    /// assert_eq!(occurences, vec![(0, 4, 9)]);
+    /// ```
+    pub fn find_within_edit_distance(&self, pattern: &[u8], max_edits: usize)
+        -> Vec<(SequenceId, usize, usize)>
+    {
+        #[allow(clippy::too_many_arguments)]
+        fn _find_within_edit_distance<'a, 'b>(
+            tree: &SuffixTree<'a, 'b>,
+            node: NodeId,
+            depth: usize,
+            column: &[usize],
+            mut best: Option<(usize, usize)>,
+            pattern: &[u8],
+            max_edits: usize,
+            result: &mut Vec<(SequenceId, usize, usize)>,
+        ) {
+            let label = tree.edge_label(node);
+            let mut column = column.to_vec();
+            let mut exhausted = false;
+
+            for (i, &symbol) in label.iter().enumerate() {
+                let length = depth + i + 1;
+
+                let mut next_column = Vec::with_capacity(column.len());
+                next_column.push(length);
+                for j in 1..column.len() {
+                    let cost = (pattern[j - 1] != symbol) as usize;
+                    let edits = (column[j - 1] + cost).min(column[j] + 1).min(next_column[j - 1] + 1);
+                    next_column.push(edits);
+                }
+                column = next_column;
+
+                let edits = column[pattern.len()];
+                match best {
+                    Some((best_edits, _)) if best_edits <= edits => {},
+                    _ if edits <= max_edits => best = Some((edits, length)),
+                    _ => {},
+                }
+
+                if column.iter().all(|&edits| edits > max_edits) {
+                    exhausted = true;
+                    break;
+                }
+            }
+
+            match tree.nodes[node] {
+                Node::Internal(InternalNode { ref children, .. }) if !exhausted => {
+                    for child in children.iter() {
+                        _find_within_edit_distance(
+                            tree, child, depth + label.len(), &column, best, pattern, max_edits, result
+                        );
+                    }
+                },
+                _ => if let Some((_, length)) = best {
+                    for (seq_id, position) in tree.node_occurences(node, 0) {
+                        let start = leaf_position(seq_id, position, depth);
+                        result.push(tree.original_range(seq_id, start, start + length));
+                    }
+                },
+            }
+        }
+
+        // Only matches of at least one byte are recorded, which for an empty
+        // pattern leaves none.
+        let mut result = Vec::new();
+        if pattern.is_empty() {
+            return result;
+        }
+
+        let column: Vec<usize> = (0..=pattern.len()).collect();
+        for child in self.root_node().children.iter() {
+            _find_within_edit_distance(self, child, 0, &column, None, pattern, max_edits, &mut result);
+        }
+
+        result
+    }
+
+    /// Returns all the occurences of the given pattern, where every `wildcard`
+    /// byte in the pattern matches any single symbol. Wildcards can match
+    /// inside of an edge as well as branch into all children of a node.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"duckquack", None);
+    /// let mut occurences = tree.find_with_wildcards(b"?ck", b'?');
+    /// occurences.sort();
+    ///
+    /// assert_eq!(occurences, vec![(0, 1, 4), (0, 6, 9)]);
+    /// ```
+    pub fn find_with_wildcards(&self, pattern: &[u8], wildcard: u8)
+        -> Vec<(SequenceId, usize, usize)>
+    {
+        fn _find_with_wildcards<'t, 'a, 'b>(
+            tree: &'t SuffixTree<'a, 'b>,
+            cursor: Cursor<'t, 'a, 'b>,
+            pattern: &[u8],
+            wildcard: u8,
+            result: &mut Vec<(SequenceId, usize, usize)>,
+        ) {
+            let symbol = match pattern.first() {
+                Some(&symbol) => symbol,
+                None => {
+                    result.extend(cursor.occurences());
+                    return;
+                },
+            };
+
+            if symbol != wildcard {
+                let mut cursor = cursor;
+                if cursor.step(symbol) {
+                    _find_with_wildcards(tree, cursor, &pattern[1..], wildcard, result);
+                }
+                return;
+            }
+
+            let label = tree.edge_label(cursor.node());
+            let symbols: Vec<u8> = if cursor.offset() < label.len() {
+                vec![label[cursor.offset()]]
+            } else {
+                tree.children(cursor.node()).filter_map(|child| tree.edge_first_char(child))
+                    .collect()
+            };
+
+            for symbol in symbols {
+                let mut next_cursor = cursor.clone();
+                if next_cursor.step(symbol) {
+                    _find_with_wildcards(tree, next_cursor, &pattern[1..], wildcard, result);
+                }
+            }
+        }
+
+        let mut result = Vec::new();
+        if let Some(cursor) = self.walk(b"") {
+            _find_with_wildcards(self, cursor, pattern, wildcard, &mut result);
+        }
+
+        result
+    }
+
+    /// Returns all the occurences of the given pattern like `find`, together
+    /// with the length of the suffix starting at each occurence. This allows
+    /// ranking matches by how close they are to the end of their sequence.
+    ///
+    /// In circular trees the suffix ends with the circular sequence, not its
+    /// doubled copy, so an occurence starting in the second copy has the same
+    /// suffix length as the one at the corresponding position of the first.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"test", None);
+    /// let mut occurences = tree.find_detailed(b"es");
+    /// assert_eq!(occurences.next(), Some((0, 1, 3, 3)));
+    /// ```
+    pub fn find_detailed<'s, 'c>(&'s self, pattern: &'c [u8])
+        -> Box<dyn Iterator<Item = (SequenceId, usize, usize, usize)> + 's>
+    {
+        Box::new(self.find(pattern).map(move |(seq_id, start, end)| {
+            let length = self.sequences[seq_id].data.len();
+            let suffix_len = if self.circular {
+                length / 2 - start % (length / 2)
+            } else {
+                length - start
+            };
+
+            (seq_id, start, end, suffix_len)
+        }))
+    }
+
+    /// Returns all the occurences of the given pattern grouped by sequence id,
+    /// with the start and end positions of each sequence in ascending order.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"abab", b"bb"], None);
+    /// let occurences = tree.find_grouped(b"b");
+    ///
+    /// assert_eq!(occurences[&0], vec![(1, 2), (3, 4)]);
+    /// assert_eq!(occurences[&1], vec![(0, 1), (1, 2)]);
+    /// assert!(tree.find_grouped(b"c").is_empty());
+    /// ```
+    pub fn find_grouped(&self, pattern: &[u8]) -> HashMap<SequenceId, Vec<(usize, usize)>> {
+        let mut groups: HashMap<SequenceId, Vec<(usize, usize)>> = HashMap::new();
+        for (seq_id, start, end) in self.find(pattern) {
+            groups.entry(seq_id).or_default().push((start, end));
+        }
+
+        for occurences in groups.values_mut() {
+            occurences.sort();
+        }
+
+        groups
+    }
+
+    /// Returns the maximum number of non-overlapping occurences of the given
+    /// pattern, summed over all sequences. Within a sequence the occurences are
+    /// taken greedily from left to right, skipping any that overlap the last
+    /// one taken, which is optimal since all of them have the same length.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"aaaa", b"aaa"], None);
+    ///
+    /// assert_eq!(tree.find(b"aa").count(), 5);
+    /// assert_eq!(tree.count_non_overlapping(b"aa"), 3);
+    /// ```
+    pub fn count_non_overlapping(&self, pattern: &[u8]) -> usize {
+        self.find_grouped(pattern).values().map(|occurences| {
+            let mut count = 0;
+            let mut last_end = 0;
+            for &(start, end) in occurences {
+                if count == 0 || start >= last_end {
+                    count += 1;
+                    last_end = end;
+                }
+            }
+
+            count
+        }).sum()
+    }
+
+    /// Returns the start positions of all occurences of the given pattern,
+    /// with consecutive start positions within a sequence merged into runs.
+    /// The runs are sorted by sequence id and position.
+    ///
+    /// A run covers the start offsets of the occurences, not the bytes they
+    /// span: the occurences of a run `r` end at `r.start + pattern.len()` up
+    /// to `r.end - 1 + pattern.len()`.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"aaaabaa", b"aa"], None);
+    ///
+    /// assert_eq!(tree.find(b"aa").count(), 5);
+    /// assert_eq!(tree.find_runs(b"aa"), vec![(0, 0..3), (0, 5..6), (1, 0..1)]);
+    /// ```
+    pub fn find_runs(&self, pattern: &[u8]) -> Vec<(SequenceId, Range<usize>)> {
+        let mut starts: Vec<(SequenceId, usize)> = self.find(pattern)
+            .map(|(seq_id, start, _)| (seq_id, start))
+            .collect();
+        starts.sort_unstable();
+
+        let mut runs: Vec<(SequenceId, Range<usize>)> = Vec::new();
+        for (seq_id, start) in starts {
+            match runs.last_mut() {
+                Some(&mut (run_seq_id, ref mut run)) if run_seq_id == seq_id && run.end == start => {
+                    run.end += 1;
+                },
+                _ => runs.push((seq_id, start..start + 1)),
+            }
+        }
+
+        runs
+    }
+
+    /// Returns the ids of all sequences that start with the given pattern, in
+    /// ascending order. In contrast to `contains`, which accepts a prefix of
+    /// any suffix, only prefixes of the original sequences are considered.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"test", b"rest", b"tester"], None);
+    ///
+    /// assert_eq!(tree.is_prefix_of_sequence(b"tes"), vec![0, 2]);
+    /// assert_eq!(tree.is_prefix_of_sequence(b"est"), vec![]);
+    /// assert_eq!(tree.is_prefix_of_sequence(b""), vec![0, 1, 2]);
+    /// ```
+    pub fn is_prefix_of_sequence(&self, pattern: &[u8]) -> Vec<SequenceId> {
+        if !self.contains(pattern) {
+            return Vec::new();
+        }
+
+        self.sequences.iter().filter(|sequence| {
+            sequence.data.starts_with(pattern)
+        }).map(|sequence| sequence.id).collect()
+    }
+
+    /// Returns the ids of all sequences containing the given pattern, in
+    /// ascending order. This only walks down to the pattern, without
+    /// enumerating its occurences.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"test", b"rest", b"toast"], None);
+    ///
+    /// assert_eq!(tree.sequences_containing(b"est"), vec![0, 1]);
+    /// assert_eq!(tree.sequences_containing(b"oa"), vec![2]);
+    /// ```
+    pub fn sequences_containing(&self, pattern: &[u8]) -> Vec<SequenceId> {
+        if pattern.is_empty() {
+            return (0..self.sequences.len()).collect();
+        }
+
+        match self.find_node(pattern) {
+            Some((node, _)) => self.sequences_under(node).collect(),
+            None => Vec::new(),
+        }
+    }
+
+    /// Returns all the occurences of the given pattern like `find`, but as
+    /// `Match` values that can be sliced out of their sequence directly.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"test", b"rest"], None);
+    /// let mut matches: Vec<_> = tree.find_matches(b"est").collect();
+    /// matches.sort_by_key(|m| m.seq_id());
+    ///
+    /// assert_eq!(matches[1].seq_id(), 1);
+    /// assert_eq!(matches[1].range(), 1..4);
+    /// assert_eq!(matches[1].as_bytes(), b"est");
+    /// ```
+    pub fn find_matches<'s, 'c>(&'s self, pattern: &'c [u8])
+        -> Box<dyn Iterator<Item = Match<'a>> + 's>
+    {
+        Box::new(self.find(pattern).map(move |(seq_id, start, end)| {
+            Match { seq_id, start, end, data: self.sequences[seq_id].original }
+        }))
+    }
+
+    /// Returns all the occurences of the given pattern that start and end on
+    /// UTF-8 character boundaries, so that every reported range can be sliced
+    /// out of the corresponding `str`.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    /// use suffix_tree::alphabet::Alphabet;
+    ///
+    /// let alphabet = Alphabet::new("näe".as_bytes());
+    /// let tree = SuffixTree::from_str("nänä", Some(alphabet));
+    /// let mut occurences = tree.find_str("än");
+    /// assert_eq!(occurences.next(), Some((0, 1, 4)));
+    /// assert_eq!(occurences.next(), None);
+    /// ```
+    pub fn find_str<'s, 'c>(&'s self, pattern: &'c str)
+        -> Box<dyn Iterator<Item = (SequenceId, usize, usize)> + 's>
+    {
+        fn is_char_boundary(data: &[u8], index: usize) -> bool {
+            // Continuation bytes of a multi-byte character look like 0b10xxxxxx.
+            index == data.len() || data[index] & 0xC0 != 0x80
+        }
+
+        Box::new(self.find(pattern.as_bytes()).filter(move |&(seq_id, start, end)| {
+            let data = self.sequences[seq_id].original;
+            is_char_boundary(data, start) && is_char_boundary(data, end)
+        }))
+    }
+
+    /// Returns the sequence id and start position of every occurence of the
+    /// given pattern as a suffix of a sequence, in ascending order. Unlike
+    /// `find`, occurences that are followed by further symbols are not
+    /// reported.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"abcab", b"cab"], None);
+    ///
+    /// assert_eq!(tree.suffixes_matching(b"ab"), vec![(0, 3), (1, 1)]);
+    /// assert_eq!(tree.suffixes_matching(b"bc"), vec![]);
+    /// ```
+    pub fn suffixes_matching(&self, pattern: &[u8]) -> Vec<(SequenceId, usize)> {
+        let (node, offset) = match self.walk(pattern) {
+            Some(cursor) => (cursor.node(), cursor.offset()),
+            None => return Vec::new(),
+        };
+
+        if offset < self.edge_len(node) {
+            return Vec::new();
+        }
+
+        let suffix_start = |seq_id: SequenceId| {
+            let end = self.sequences[seq_id].data.len();
+            let (seq_id, start, _) = self.original_range(seq_id, match_start(seq_id, end, pattern.len()), end);
+            (seq_id, start)
+        };
+        let mut positions: Vec<(SequenceId, usize)> = match self.nodes[node] {
+            Node::Leaf(LeafNode { seq_id, .. }) => vec![suffix_start(seq_id)],
+            Node::Root(RootNode { ref children })
+            | Node::Internal(InternalNode { ref children, .. }) => {
+                children.terminals.keys().map(|&seq_id| suffix_start(seq_id)).collect()
+            },
+        };
+        positions.sort();
+
+        positions
+    }
+
+    /// Returns the occurences of the pattern that ends `offset` symbols into
+    /// the edge leading to `node` and has the given length.
+    fn occurences_of_node<'s>(&'s self, node: NodeId, offset: usize, length: usize)
+        -> Occurences<'s>
+    {
+        if node == 0 {
+            return Occurences::empty();
         }
+
+        Occurences::from_subtree(self, node, offset, length)
     }
 
-    fn internal_node_mut(&mut self, node_id: NodeId) -> Option<&mut InternalNode> {
-        if let Node::Internal(ref mut node) = self.nodes[node_id] {
-            Some(node)
+    /// Maps a range of a stored sequence to the original sequence, which for
+    /// reversed trees is the range covering the same bytes from the other end.
+    fn original_range(&self, seq_id: SequenceId, start: usize, end: usize)
+        -> (SequenceId, usize, usize)
+    {
+        if self.reversed {
+            let length = self.sequences[seq_id].data.len();
+            (seq_id, length - end, length - start)
         } else {
-            None
+            (seq_id, start, end)
         }
     }
 
-    fn prepare_lcs(&self) {
-        fn _prepare_lcs<'b, 'c>(tree: &SuffixTree<'b, 'c>, node: NodeId) -> u128 {
-            match tree.nodes[node] {
-                Node::Root(_) => panic!(),
-                Node::Internal(InternalNode { ref children, ref sequence_id_set, .. }) => {
-                    let mut id_set = 0;
-                    for child in children.iter() {
-                        id_set |= _prepare_lcs(tree, child);
-                    }
-
-                    sequence_id_set.set(Some(id_set));
+    /// Returns whether the tree was built over reversed sequences, see
+    /// `SuffixTreeBuilder::with_reversed`.
+    pub fn is_reversed(&self) -> bool {
+        self.reversed
+    }
 
-                    id_set
-                },
-                Node::Leaf(LeafNode { seq_id, .. }) => 1 << seq_id,
-            }
-        }
+    /// Returns whether the tree was built over doubled circular sequences,
+    /// see `SuffixTreeBuilder::with_circular`.
+    pub fn is_circular(&self) -> bool {
+        self.circular
+    }
 
-        for child in self.root_node().children.iter() {
-            _prepare_lcs(self, child);
-        }
+    fn node_occurences<'s>(&'s self, node: NodeId, depth: usize) -> SubtreeLeaves<'s> {
+        SubtreeLeaves::new(self, node, depth)
     }
 
-    /// Returns all occurences of the longest common subsequence in suffix tree.
-    /// If there are multiple such subsequences it just returns the occurences
-    /// of a random one.
+    /// Returns a cursor positioned after the given prefix, or `None` when the
+    /// prefix is not contained in the suffix tree. The cursor can then be
+    /// extended one byte at a time without walking down from the root again.
     ///
     /// #Examples
     /// ```
     /// use suffix_tree::SuffixTree;
     ///
-    /// let mut tree = SuffixTree::from_sequences(&[b"test", b"rest", b"estland"], None);
-    /// let mut occurences = tree.longest_common_subsequence();
-    /// for (seq_id, start, end) in occurences {
-    ///     assert_eq!(&tree.sequence_by_id(seq_id)[start..end], b"est")
-    /// }
+    /// let tree = SuffixTree::from_sequence(b"testing", None);
+    /// let mut cursor = tree.walk(b"te").unwrap();
+    ///
+    /// assert!(cursor.step(b's'));
+    /// assert!(!cursor.step(b'x'));
+    /// assert_eq!(cursor.depth(), 3);
+    /// assert_eq!(cursor.occurences().next(), Some((0, 0, 3)));
     /// ```
-    pub fn longest_common_subsequence<'s>(&'s self)
-        -> Box<Iterator<Item = (SequenceId, usize, usize)> + 's>
-    {
-        fn _longest_common_subsequence<'a, 'b>(
-            tree: &SuffixTree<'a, 'b>,
-            node: NodeId, depth: usize
-        ) -> Option<(NodeId, usize)> {
-            match tree.nodes[node] {
-                Node::Internal(InternalNode {
-                    start,
-                    end,
-                    ref sequence_id_set,
-                    ref children, 
-                    ..
-                }) => {
-                    let all_bits_set = u128::max_value() >> (128 - tree.sequences.len());
-                    if sequence_id_set.get().unwrap() != all_bits_set {
-                        return None;
-                    }
-
-                    let edge_length = end - start;
-                    children.iter().filter_map(|child| {
-                        _longest_common_subsequence(tree, child, depth + edge_length)
-                    }).max_by_key(|&(_, depth)| {
-                        depth
-                    }).or_else(|| {
-                        Some((node, depth + edge_length))
-                    })
-                },
-                Node::Leaf(_) => None,
-                Node::Root(_) => panic!(),
-            }
+    pub fn walk<'s>(&'s self, prefix: &[u8]) -> Option<Cursor<'s, 'a, 'b>> {
+        if prefix.is_empty() {
+            return Some(Cursor::new(self, 0, 0, 0));
         }
 
-        let maybe_node = self.root_node().children.iter().filter_map(|child| {
-            _longest_common_subsequence(self, child, 0)
-        }).max_by_key(|&(_, depth)| depth);
-
-        if let Some((node, depth)) = maybe_node {
-            let edge_length = {
-                let internal = self.internal_node(node).unwrap();
-                internal.end - internal.start
-            };
-
-            Box::new(self.node_occurences(node, 0).map(move |(seq_id, position)| {
-                let end = position + edge_length;
-                let start = end - depth;
-                (seq_id, start, end) 
-            }))
-        } else {
-            Box::new(iter::empty())
+        if self.max_depth.is_some_and(|max_depth| prefix.len() > max_depth) {
+            return None;
         }
-    }
 
+        self.find_node(prefix).map(|(node, offset)| Cursor::new(self, node, offset, prefix.len()))
+    }
 
-    /// Returns true when the given pattern is contained in the suffix tree. 
+    /// Returns the label of the edge leading into the given node, without the
+    /// terminal symbol. Unlike the labels shown by `pretty_print`, this borrows
+    /// from the sequence data instead of allocating.
     ///
     /// #Examples
     /// ```
     /// use suffix_tree::SuffixTree;
     ///
-    /// let tree = SuffixTree::from_sequence(b"test", None);
-    ///
-    /// assert!(tree.contains(b"es"));
-    /// assert!(!tree.contains(b"asdf"));
+    /// let tree = SuffixTree::from_sequence(b"abab", None);
+    /// let cursor = tree.walk(b"b").unwrap();
+    /// assert_eq!(tree.edge_label(cursor.node()), b"b");
     /// ```
-    pub fn contains(&self, pattern: &[u8]) -> bool {
-        self.find_node(pattern).is_some()
+    pub fn edge_label(&self, node: NodeId) -> &'a [u8] {
+        match self.nodes[node] {
+            Node::Root(_) => &[],
+            Node::Internal(InternalNode { seq_id, start, end, .. }) => {
+                &self.sequences[seq_id].data[start..end]
+            },
+            Node::Leaf(LeafNode { seq_id, start, .. }) => {
+                &self.sequences[seq_id].data[start..]
+            }
+        }
     }
 
-    /// Returns all the occurences of the given pattern in the suffix tree. 
+    /// Returns the first byte of the label of the edge leading into the given
+    /// node, or `None` for the root and for leaves whose edge only holds the
+    /// terminal symbol.
     ///
     /// #Examples
     /// ```
     /// use suffix_tree::SuffixTree;
     ///
-    /// let tree = SuffixTree::from_sequence(b"test", None);
-    /// let mut occurences = tree.find(b"es");
-    /// assert_eq!(occurences.next(), Some((0, 1, 3)));
-    /// assert_eq!(occurences.next(), None);
+    /// let tree = SuffixTree::from_sequence(b"abab", None);
+    /// let firsts: Vec<Option<u8>> = tree.children(0).map(|child| tree.edge_first_char(child)).collect();
+    /// assert_eq!(firsts, vec![None, Some(b'a'), Some(b'b')]);
     /// ```
-    pub fn find<'s, 'c>(&'s self, pattern: &'c [u8])
-        -> Box<Iterator<Item = (SequenceId, usize, usize)> + 's>
-    {
-        if let Some((node, remaining)) = self.find_node(pattern) {
-            let pattern_len = pattern.len();
-
-            Box::new(self.node_occurences(node, 0).map(move |(seq_id, position)| {
-                let end = position + remaining;
-                let start = end - pattern_len;
-                (seq_id, start, end) 
-            }))
-        } else {
-            Box::new(iter::empty())
+    pub fn edge_first_char(&self, node: NodeId) -> Option<u8> {
+        match self.nodes[node] {
+            Node::Root(_) => None,
+            Node::Internal(InternalNode { seq_id, start, .. }) |
+            Node::Leaf(LeafNode { seq_id, start, .. }) => self.sequences[seq_id].data.get(start).cloned(),
         }
     }
 
-    fn node_occurences<'s>(&'s self, node: NodeId, depth: usize)
-        -> Box<Iterator<Item = (SequenceId, usize)> + 's>
-    {
+    /// Returns the length of the label of the edge leading into the given
+    /// node, which for leaves extends to the end of the sequence without the
+    /// terminal symbol.
+    pub fn edge_len(&self, node: NodeId) -> usize {
         match self.nodes[node] {
-            Node::Root(_) => Box::new(iter::empty()),
-            Node::Internal(InternalNode { start, end, ref children, .. }) => {
-                let edge_length = end - start;
+            Node::Root(_) => 0,
+            Node::Internal(InternalNode { start, end, .. }) => end - start,
+            Node::Leaf(LeafNode { seq_id, start, .. }) => self.sequences[seq_id].data.len() - start,
+        }
+    }
 
-                Box::new(children.iter().flat_map(move |child| {
-                    self.node_occurences(child, depth + edge_length)
-                }))
-            },
-            Node::Leaf(LeafNode { seq_id, start, .. }) => {
-                Box::new(iter::once((seq_id, start - depth)))
-            }
+    /// Like `find`, but with the rank of every symbol of the pattern already
+    /// looked up, see `PreparedPatterns`.
+    pub(crate) fn find_ranked<'s>(&'s self, pattern: &[u8], ranks: &[u8]) -> Occurences<'s> {
+        if pattern.is_empty() || self.max_depth.is_some_and(|max_depth| pattern.len() > max_depth) {
+            return Occurences::empty();
+        }
+
+        let found = self.find_node_by(pattern, |node, depth| {
+            self.nodes[node].children().unwrap().get_regular_child(ranks[depth])
+        });
+        match found {
+            Some((node, offset)) => Cursor::new(self, node, offset, pattern.len()).occurences(),
+            None => Occurences::empty(),
         }
     }
 
     fn find_node(&self, pattern: &[u8]) -> Option<(NodeId, usize)> {
+        self.find_node_by(pattern, |node, depth| self.get_child(node, Symbol::Regular(pattern[depth])))
+    }
+
+    // Follows the pattern from the root, looking up the child for the symbol
+    // at the given depth with `child_at`.
+    fn find_node_by<F>(&self, pattern: &[u8], child_at: F) -> Option<(NodeId, usize)>
+    where
+        F: Fn(NodeId, usize) -> Option<NodeId>
+    {
         let mut current_node = 0;
         let mut remaining = pattern.len();
 
         loop {
             let depth = pattern.len() - remaining;
-            let next_symbol = Symbol::Regular(pattern[depth]);
 
-            if let Some(child) = self.get_child(current_node, next_symbol) {
-                let label = match self.nodes[child] {
-                    Node::Root(_) => panic!(),
-                    Node::Internal(InternalNode { seq_id, start, end, .. }) => {
-                        &self.sequences[seq_id].data[start..end]
-                    },
-                    Node::Leaf(LeafNode { seq_id, start, .. }) => {
-                        &self.sequences[seq_id].data[start..]
-                    }
-                };
+            if let Some(child) = child_at(current_node, depth) {
+                let label = self.edge_label(child);
 
                 current_node = child;
 
@@ -534,27 +3099,488 @@ pub struct SuffixTreeBuilder<'a, 'b> {
     remaining: usize,
 
     previously_created_node: Option<NodeId>,
+
+    // Maps the data of every added sequence to its first id. Hashing every
+    // sequence is only worth it when duplicates are looked up, so the map is
+    // built from the tree on first use and kept up to date from then on.
+    sequence_ids: Option<HashMap<&'a [u8], SequenceId>>,
+    spare_child_maps: Vec<ChildMap>,
+    child_storage: ChildStorage,
+    max_depth: Option<usize>,
+    terminal_policy: TerminalPolicy,
+    metrics: Option<BuildMetrics>,
+}
+
+impl<'a, 'b> fmt::Debug for SuffixTreeBuilder<'a, 'b> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("SuffixTreeBuilder")
+            .field("tree", &self.tree)
+            .field("active_node", &self.active_node)
+            .field("active_edge", &self.active_edge)
+            .field("position", &self.position)
+            .field("remaining", &self.remaining)
+            .finish()
+    }
 }
 
 impl<'a, 'b> SuffixTreeBuilder<'a, 'b> {
     pub fn new(alphabet: Option<Alphabet<'b>>) -> SuffixTreeBuilder<'a, 'b> {
+        SuffixTreeBuilder::from_tree(SuffixTree::new(alphabet))
+    }
+
+    /// Creates a builder accepting every byte but 255, for corpora whose
+    /// symbols are not known in advance. The children of internal nodes are
+    /// stored sparsely, so each node only pays for the symbols that actually
+    /// follow it instead of for the whole alphabet.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTreeBuilder;
+    ///
+    /// let mut builder = SuffixTreeBuilder::new_permissive();
+    /// builder.add_sequence(b"GATTACA");
+    /// builder.add_sequence(b"MKT-LLV");
+    /// builder.add_sequence(&[0, 1, 2, 254]);
+    /// let tree = builder.build();
+    ///
+    /// assert_eq!(tree.sequences_containing(b"T"), vec![0, 1]);
+    /// assert!(tree.contains(&[1, 2]));
+    /// ```
+    pub fn new_permissive() -> SuffixTreeBuilder<'a, 'b> {
+        SuffixTreeBuilder::new(Some(Alphabet::bytes())).with_child_storage(ChildStorage::Sparse)
+    }
+
+    fn from_tree(mut tree: SuffixTree<'a, 'b>) -> SuffixTreeBuilder<'a, 'b> {
+        // Nodes whose suffix links were dropped are left without one, which
+        // only makes the construction rescan from the root when reaching them.
+        tree.suffix_links.resize(tree.nodes.len(), None);
+
         SuffixTreeBuilder {
-            tree: SuffixTree::new(alphabet),
+            tree,
             active_node: 0,
             active_edge: None,
             position: 0,
             remaining: 0,
-            previously_created_node: None
+            previously_created_node: None,
+            sequence_ids: None,
+            spare_child_maps: Vec::new(),
+            child_storage: ChildStorage::default(),
+            max_depth: None,
+            terminal_policy: TerminalPolicy::default(),
+            metrics: None,
+        }
+    }
+
+    /// Sets how the children of internal nodes created from now on are stored.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::{ChildStorage, SuffixTreeBuilder};
+    ///
+    /// let mut builder = SuffixTreeBuilder::new(None).with_child_storage(ChildStorage::Sparse);
+    /// builder.add_sequence(b"banana");
+    /// let tree = builder.build();
+    ///
+    /// assert_eq!(tree.find(b"ana").count(), 2);
+    /// ```
+    pub fn with_child_storage(mut self, child_storage: ChildStorage) -> SuffixTreeBuilder<'a, 'b> {
+        // Maps kept by `reset` use the previous storage and must not be reused.
+        if child_storage != self.child_storage {
+            self.spare_child_maps.clear();
+        }
+        self.child_storage = child_storage;
+        self
+    }
+
+    /// Limits the built tree to patterns of at most `max_depth` bytes. Longer
+    /// patterns are never found, and `walk` returns `None` for them. In return
+    /// the internal nodes deeper than `max_depth` are dropped, which for long
+    /// sequences is a large part of the tree.
+    ///
+    /// The full tree is built first and truncated in `build`, so this lowers
+    /// the memory held by the finished tree, not the peak during construction.
+    /// A truncated tree cannot be resumed.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTreeBuilder;
+    ///
+    /// let mut builder = SuffixTreeBuilder::new(None).with_max_depth(3);
+    /// builder.add_sequence(b"abcabcabc");
+    /// let tree = builder.build();
+    ///
+    /// assert_eq!(tree.max_depth(), Some(3));
+    /// assert_eq!(tree.find(b"bca").count(), 2);
+    /// assert!(tree.walk(b"abca").is_none());
+    /// ```
+    pub fn with_max_depth(mut self, max_depth: usize) -> SuffixTreeBuilder<'a, 'b> {
+        self.max_depth = Some(max_depth);
+        self
+    }
+
+    /// Makes the builder count the steps of the construction, see `metrics`.
+    /// This adds a check to every step, so it is off by default.
+    pub fn with_metrics(mut self) -> SuffixTreeBuilder<'a, 'b> {
+        self.metrics = Some(BuildMetrics::default());
+        self
+    }
+
+    /// Returns the counts of the steps taken to add the sequences so far, or
+    /// `None` unless the builder was created `with_metrics`.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTreeBuilder;
+    ///
+    /// let mut builder = SuffixTreeBuilder::new(None).with_metrics();
+    /// builder.add_sequence(b"banana");
+    /// let metrics = builder.metrics().unwrap();
+    ///
+    /// // Six bytes and the terminal, one leaf per suffix including the empty one.
+    /// assert_eq!(metrics.symbols_processed, 7);
+    /// assert_eq!(metrics.leaf_insertions, 7);
+    /// // The internal nodes "a", "ana" and "na".
+    /// assert_eq!(metrics.edge_splits, 3);
+    /// ```
+    pub fn metrics(&self) -> Option<BuildMetrics> {
+        self.metrics
+    }
+
+    /// Sets how sequences identical to one added before are handled, which
+    /// also applies to sequences added to the tree before it was resumed.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::{AddSequenceError, SuffixTreeBuilder, TerminalPolicy};
+    ///
+    /// let mut builder = SuffixTreeBuilder::new(None).with_terminal_policy(TerminalPolicy::Shared);
+    /// assert_eq!(builder.try_add_sequence(b"test"), Ok(0));
+    /// assert_eq!(builder.try_add_sequence(b"test"), Ok(0));
+    /// assert_eq!(builder.build().find(b"est").count(), 1);
+    ///
+    /// let mut builder = SuffixTreeBuilder::new(None).with_terminal_policy(TerminalPolicy::Error);
+    /// builder.add_sequence(b"test");
+    /// assert_eq!(builder.try_add_sequence(b"test"), Err(AddSequenceError::Duplicate(0)));
+    /// ```
+    pub fn with_terminal_policy(mut self, terminal_policy: TerminalPolicy) -> SuffixTreeBuilder<'a, 'b> {
+        self.terminal_policy = terminal_policy;
+        self
+    }
+
+    /// Marks the sequences as reversed copies of the original sequences, so
+    /// that the tree indexes them from right to left. Patterns are matched
+    /// against the stored, reversed bytes, so a pattern has to be reversed as
+    /// well to find the original, and extending a cursor extends the match to
+    /// the left.
+    ///
+    /// Every position the tree reports refers to the original sequence, as do
+    /// `sequence_by_id` and the bytes of a `Match`. Only the methods describing
+    /// the stored bytes themselves, like `edge_label`, `leaf_suffix`, `bwt`,
+    /// `leaves_by_sequence`, `top_k_frequent`, `tandem_repeats`, `lz78_parse`,
+    /// `pretty_print` and `to_json`, work on the reversed sequences.
+    ///
+    /// The builder borrows the sequences, so they are added with
+    /// `add_reversed_sequence` along with a reversed copy made by the caller.
+    /// `SuffixTree::from_sequence_reversed` does so and keeps both copies.
+    ///
+    /// Panics if the orientation is changed after sequences were added.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTreeBuilder;
+    ///
+    /// let reversed: Vec<u8> = b"abcab".iter().rev().cloned().collect();
+    /// let mut builder = SuffixTreeBuilder::new(None).with_reversed(true);
+    /// builder.add_reversed_sequence(b"abcab", &reversed);
+    /// let tree = builder.build();
+    ///
+    /// // "cb" read from right to left is "bc", which occurs at 1..3 in "abcab".
+    /// assert_eq!(tree.find(b"cb").collect::<Vec<_>>(), vec![(0, 1, 3)]);
+    /// assert_eq!(tree.sequence_by_id(0), b"abcab");
+    /// ```
+    pub fn with_reversed(mut self, reversed: bool) -> SuffixTreeBuilder<'a, 'b> {
+        assert!(
+            reversed == self.tree.reversed || self.tree.sequences.is_empty(),
+            "The orientation of a tree cannot change once it has sequences."
+        );
+
+        self.tree.reversed = reversed;
+        self
+    }
+
+    /// Marks the sequences as circular, each one being added as two copies of
+    /// the circular sequence in a row. Every rotation of the circular sequence
+    /// is then a substring, so `SuffixTree::find_circular` finds patterns
+    /// wrapping around its end.
+    ///
+    /// The builder borrows the sequences, so they have to be doubled by the
+    /// caller. `SuffixTree::from_circular` does so and keeps the doubled copy.
+    ///
+    /// Panics if the setting is changed after sequences were added, and adding
+    /// a sequence panics if it is not made of two equal halves.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTreeBuilder;
+    ///
+    /// let mut builder = SuffixTreeBuilder::new(None).with_circular(true);
+    /// builder.add_sequence(b"abcdabcd");
+    /// let tree = builder.build();
+    ///
+    /// assert_eq!(tree.find_circular(b"dab"), vec![(0, 3, 6)]);
+    /// ```
+    pub fn with_circular(mut self, circular: bool) -> SuffixTreeBuilder<'a, 'b> {
+        assert!(
+            circular == self.tree.circular || self.tree.sequences.is_empty(),
+            "A tree cannot become circular or stop being circular once it has sequences."
+        );
+
+        self.tree.circular = circular;
+        self
+    }
+
+    /// Removes all sequences, so that the builder can be used to construct an
+    /// unrelated tree. The child maps of the removed nodes are kept around and
+    /// reused, which saves allocations when building many small trees.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTreeBuilder;
+    ///
+    /// let mut builder = SuffixTreeBuilder::new(None);
+    /// for sequence in &[b"test", b"rest"] {
+    ///     builder.add_sequence(*sequence);
+    ///     let tree = builder.build();
+    ///     assert_eq!(tree.pretty_print().lines().count(), 5);
+    ///
+    ///     builder = tree.resume();
+    ///     builder.reset();
+    /// }
+    /// ```
+    pub fn reset(&mut self) {
+        for node in self.tree.nodes.drain(1..) {
+            if let Node::Internal(InternalNode { mut children, .. }) = node {
+                children.clear();
+                self.spare_child_maps.push(children);
+            }
+        }
+        self.tree.nodes[0].children_mut().unwrap().clear();
+
+        self.tree.sequences.clear();
+        self.tree.suffix_links.truncate(1);
+        self.tree.string_depths.clear();
+        self.tree.leaf_counts.clear();
+        self.tree.sequence_id_sets.clear();
+        self.sequence_ids = None;
+
+        self.active_node = 0;
+        self.active_edge = None;
+        self.position = 0;
+        self.remaining = 0;
+        self.previously_created_node = None;
+
+        if let Some(ref mut metrics) = self.metrics {
+            *metrics = BuildMetrics::default();
         }
     }
 
+    /// Returns the tree built so far. Its nodes and edges are complete for every
+    /// added sequence, but the statistics computed by `build` are missing, so
+    /// only structural queries like `children`, `edge_label`, `walk` and
+    /// `pretty_print` should be used on it.
+    pub fn tree(&self) -> &SuffixTree<'a, 'b> {
+        &self.tree
+    }
+
+    fn new_child_map(&mut self) -> ChildMap {
+        let alphabet_size = self.tree.alphabet.size;
+        let child_storage = self.child_storage;
+        self.spare_child_maps.pop().unwrap_or_else(|| ChildMap::new(alphabet_size, child_storage))
+    }
+
     pub fn build(self) -> SuffixTree<'a, 'b> {
-        self.tree.prepare_lcs();
-        self.tree
+        let mut tree = self.tree;
+        if let Some(max_depth) = self.max_depth {
+            tree.truncate(max_depth, self.child_storage);
+        }
+        tree.prepare_lcs();
+        tree.prepare_node_statistics();
+        tree
+    }
+
+    /// Adds the sequence unless an identical one was added before, and returns
+    /// the id under which its data can be found in the built tree.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTreeBuilder;
+    ///
+    /// let mut builder = SuffixTreeBuilder::new(None);
+    /// assert_eq!(builder.add_sequence_dedup(b"test"), 0);
+    /// assert_eq!(builder.add_sequence_dedup(b"rest"), 1);
+    /// assert_eq!(builder.add_sequence_dedup(b"test"), 0);
+    ///
+    /// let tree = builder.build();
+    /// assert_eq!(tree.sequence_by_id(1), b"rest");
+    /// ```
+    pub fn add_sequence_dedup(&mut self, sequence: &'a [u8]) -> SequenceId {
+        if let Some(&seq_id) = self.sequence_ids().get(sequence) {
+            return seq_id;
+        }
+
+        self.add_sequence(sequence);
+        self.tree.current_sequence().id
+    }
+
+    /// Adds every part of `data` delimited by one of the `separators` as a
+    /// separate sequence, so that matches never span a separator. A trailing
+    /// separator does not start another sequence. Returns the ids of the added
+    /// sequences, whose positions are relative to the start of each part.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTreeBuilder;
+    ///
+    /// let mut builder = SuffixTreeBuilder::new(None);
+    /// assert_eq!(builder.add_concatenated(b"test$rest#", b"$#"), 0..2);
+    ///
+    /// let tree = builder.build();
+    /// assert_eq!(tree.sequence_by_id(1), b"rest");
+    /// assert!(!tree.contains(b"tr"));
+    /// ```
+    pub fn add_concatenated(&mut self, data: &'a [u8], separators: &[u8]) -> Range<SequenceId> {
+        let data = match data.last() {
+            Some(symbol) if separators.contains(symbol) => &data[..data.len() - 1],
+            _ => data,
+        };
+
+        let first_id = self.tree.sequences.len();
+        for sequence in data.split(|symbol| separators.contains(symbol)) {
+            self.add_sequence(sequence);
+        }
+
+        first_id..self.tree.sequences.len()
+    }
+
+    /// Adds the sequence and returns its id, or an error if the sequence
+    /// contains a symbol outside the alphabet or is a duplicate rejected by
+    /// the terminal policy. The builder is left unchanged in that case. With
+    /// `TerminalPolicy::Shared`, the id of an identical sequence added before
+    /// is returned instead of adding it again.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::{AddSequenceError, SuffixTreeBuilder};
+    /// use suffix_tree::alphabet::Alphabet;
+    ///
+    /// let mut builder = SuffixTreeBuilder::new(Some(Alphabet::new(b"ACGT")));
+    /// assert_eq!(builder.try_add_sequence(b"GATTACA"), Ok(0));
+    ///
+    /// match builder.try_add_sequence(b"GANTACA") {
+    ///     Err(AddSequenceError::InvalidSymbol(error)) => {
+    ///         assert_eq!((error.seq_id, error.position, error.symbol), (1, 2, b'N'));
+    ///     },
+    ///     _ => panic!(),
+    /// }
+    /// ```
+    pub fn try_add_sequence(&mut self, sequence: &'a [u8]) -> Result<SequenceId, AddSequenceError> {
+        assert!(!self.tree.reversed, "Sequences of a reversed tree are added with add_reversed_sequence.");
+        self.try_add(sequence, sequence)
+    }
+
+    /// Adds a sequence to a reversed tree, see `with_reversed`. The tree
+    /// indexes `reversed`, which has to hold the bytes of `sequence` in
+    /// reverse order, while positions and matches refer to `sequence`.
+    ///
+    /// Panics if the tree is not reversed, if `reversed` is not the reverse of
+    /// `sequence`, or for the same reasons as `add_sequence`.
+    pub fn add_reversed_sequence(&mut self, sequence: &'a [u8], reversed: &'a [u8]) {
+        assert!(self.tree.reversed, "Only a reversed tree takes reversed sequences.");
+        assert!(
+            sequence.iter().eq(reversed.iter().rev()),
+            "The reversed sequence has to hold the bytes of the sequence in reverse order."
+        );
+
+        if let Err(error) = self.try_add(reversed, sequence) {
+            panic!("{}", error);
+        }
+    }
+
+    // Adds a sequence of another tree with the same orientation.
+    fn add_sequence_of(&mut self, sequence: &Sequence<'a>) {
+        if let Err(error) = self.try_add(sequence.data, sequence.original) {
+            panic!("{}", error);
+        }
+    }
+
+    // Symbols are checked in the original sequence, so that the position of
+    // an invalid one is reported in the orientation the caller knows, while
+    // duplicates are looked up by the stored data.
+    fn try_add(&mut self, data: &'a [u8], original: &'a [u8]) -> Result<SequenceId, AddSequenceError> {
+        if self.tree.circular {
+            // For an odd length the second half is longer, so they differ.
+            let (first, second) = original.split_at(original.len() / 2);
+            assert!(
+                first == second,
+                "A sequence of a circular tree has to hold the circular sequence twice in a row."
+            );
+        }
+
+        self.validate(original)?;
+
+        if self.terminal_policy != TerminalPolicy::Distinct {
+            if let Some(&seq_id) = self.sequence_ids().get(data) {
+                match self.terminal_policy {
+                    TerminalPolicy::Distinct => {},
+                    TerminalPolicy::Shared => return Ok(seq_id),
+                    TerminalPolicy::Error => return Err(AddSequenceError::Duplicate(seq_id)),
+                }
+            }
+        }
+
+        self.add_valid_sequence(data, original);
+        Ok(self.tree.current_sequence().id)
+    }
+
+    fn sequence_ids(&mut self) -> &HashMap<&'a [u8], SequenceId> {
+        let sequences = &self.tree.sequences;
+        self.sequence_ids.get_or_insert_with(|| {
+            // Inserted in reverse, so that the first of several identical
+            // sequences ends up in the map.
+            sequences.iter().rev().map(|sequence| (sequence.data, sequence.id)).collect()
+        })
+    }
+
+    fn validate(&self, sequence: &[u8]) -> Result<(), InvalidSymbolError> {
+        match sequence.iter().position(|&symbol| !self.tree.alphabet.contains(symbol)) {
+            Some(position) => Err(InvalidSymbolError {
+                seq_id: self.tree.sequences.len(),
+                position,
+                symbol: sequence[position],
+            }),
+            None => Ok(()),
+        }
     }
 
+    /// Adds the sequence to the tree, following the terminal policy for
+    /// duplicates.
+    ///
+    /// Panics if the sequence contains a symbol outside the alphabet or is a
+    /// rejected duplicate, see `try_add_sequence` for a fallible version. In a
+    /// circular tree it also panics if the sequence is not doubled, see
+    /// `with_circular`.
     pub fn add_sequence(&mut self, sequence: &'a [u8]) {
-        self.tree.add_sequence(sequence);
+        if let Err(error) = self.try_add_sequence(sequence) {
+            panic!("{}", error);
+        }
+    }
+
+    fn add_valid_sequence(&mut self, data: &'a [u8], original: &'a [u8]) {
+        self.tree.add_sequence(data, original);
+        let seq_id = self.tree.current_sequence().id;
+        if let Some(ref mut sequence_ids) = self.sequence_ids {
+            sequence_ids.entry(data).or_insert(seq_id);
+        }
 
         self.position = 0;
         self.remaining = 0;
@@ -562,6 +3588,8 @@ impl<'a, 'b> SuffixTreeBuilder<'a, 'b> {
         self.active_node = 0;
         self.active_edge = None;
 
+        // The terminal symbol is inserted too, so that every suffix ends at a
+        // leaf.
         for _ in 0..self.tree.current_sequence().len() {
             self.insert_next_symbol();
         }
@@ -570,6 +3598,9 @@ impl<'a, 'b> SuffixTreeBuilder<'a, 'b> {
     fn insert_next_symbol(&mut self) {
         self.remaining += 1;
         self.previously_created_node = None;
+        if let Some(ref mut metrics) = self.metrics {
+            metrics.symbols_processed += 1;
+        }
 
         let next_symbol = self.tree.current_sequence().at(self.position);
         for _ in 0..self.remaining {
@@ -603,6 +3634,9 @@ impl<'a, 'b> SuffixTreeBuilder<'a, 'b> {
             let leaf_node = Node::new_leaf(self.tree.current_sequence().id, self.position);
             let leaf_node_id = self.tree.add_node(leaf_node);
             self.tree.add_child(self.active_node, next_symbol, leaf_node_id);
+            if let Some(ref mut metrics) = self.metrics {
+                metrics.leaf_insertions += 1;
+            }
 
             if self.active_node != 0 {
                 let active_node = self.active_node;
@@ -622,7 +3656,7 @@ impl<'a, 'b> SuffixTreeBuilder<'a, 'b> {
         let active_edge_node = self.active_edge_node();
         let (active_seq_id, active_start) = match self.tree.nodes[active_edge_node] {
             Node::Internal(InternalNode { seq_id, start, .. })
-            | Node::Leaf(LeafNode { seq_id, start }) => (seq_id, start),
+            | Node::Leaf(LeafNode { seq_id, start, .. }) => (seq_id, start),
             Node::Root(_) => panic!(),
         };
         let split_position = active_start + active_length;
@@ -640,7 +3674,7 @@ impl<'a, 'b> SuffixTreeBuilder<'a, 'b> {
 
             let node_a = {
                 let node = Node::new_internal(
-                    self.tree.alphabet.size,
+                    self.new_child_map(),
                     active_seq_id,
                     active_start,
                     split_position
@@ -659,6 +3693,10 @@ impl<'a, 'b> SuffixTreeBuilder<'a, 'b> {
                 self.tree.add_node(Node::new_leaf(seq_id, start))
             };
             self.tree.add_child(node_a, next_symbol, node_b);
+            if let Some(ref mut metrics) = self.metrics {
+                metrics.edge_splits += 1;
+                metrics.leaf_insertions += 1;
+            }
 
             self.set_suffix_link(node_a);
             self.previously_created_node = Some(node_a);
@@ -677,16 +3715,21 @@ impl<'a, 'b> SuffixTreeBuilder<'a, 'b> {
                     ));
                 };
             },
-            Node::Internal(InternalNode { suffix_link: Some(node), .. }) => {
-                self.active_node = node;
+            Node::Internal(_) | Node::Leaf(_) => match self.tree.suffix_link(self.active_node) {
+                Some(node) => {
+                    self.active_node = node;
+                    if let Some(ref mut metrics) = self.metrics {
+                        metrics.suffix_link_follows += 1;
+                    }
+                },
+                None => {
+                    self.active_node = 0;
+                    self.active_edge = Some((
+                        self.tree.current_sequence().at(self.position + 2 - self.remaining),
+                        self.remaining - 2 
+                    ));
+                },
             },
-            Node::Internal(_) | Node::Leaf(_) => {
-                self.active_node = 0;
-                self.active_edge = Some((
-                    self.tree.current_sequence().at(self.position + 2 - self.remaining),
-                    self.remaining - 2 
-                ));
-            }
         }
 
         self.normalize_active_point();
@@ -734,7 +3777,7 @@ impl<'a, 'b> SuffixTreeBuilder<'a, 'b> {
 
     fn set_suffix_link(&mut self, link_to: NodeId) {
         if let Some(node) = self.previously_created_node {
-            self.tree.internal_node_mut(node).unwrap().suffix_link = Some(link_to);
+            self.tree.suffix_links[node] = Some(link_to);
         }
 
         self.previously_created_node = None;
@@ -744,12 +3787,6 @@ impl<'a, 'b> SuffixTreeBuilder<'a, 'b> {
         let (active_symbol, _) = self.active_edge.unwrap();
         self.tree.get_child(self.active_node, active_symbol).unwrap()
     }
-
-    #[allow(dead_code)]
-    fn print_ukkonen_state(&self) {
-        println!("active_node is {}, active_edge is {:?}", self.active_node, self.active_edge);
-        println!("position is {}, remaining is {}", self.position, self.remaining);
-    }
 }
 
 pub fn longest_common_subsequence<'a>(sequences: &'a [&'a [u8]], alphabet: Option<Alphabet>)
@@ -763,3 +3800,37 @@ pub fn longest_common_subsequence<'a>(sequences: &'a [&'a [u8]], alphabet: Optio
         &tree.sequence_by_id(seq_id)[start..end]
     })
 }
+
+/// Reads every reader to its end and returns the longest substring common to
+/// all of them. The result is copied out, as the tree does not outlive the
+/// buffers it was built from.
+///
+/// #Examples
+/// ```
+/// use suffix_tree::longest_common_substring_from_readers;
+///
+/// let readers: Vec<&[u8]> = vec![b"test", b"rest", b"estland"];
+/// let lcs = longest_common_substring_from_readers(readers, None).unwrap();
+///
+/// assert_eq!(lcs, Some(b"est".to_vec()));
+/// ```
+#[cfg(feature = "std")]
+pub fn longest_common_substring_from_readers<R, I>(readers: I, alphabet: Option<Alphabet>)
+    -> io::Result<Option<Vec<u8>>>
+    where R: BufRead, I: IntoIterator<Item = R>
+{
+    let mut sequences = Vec::new();
+    for mut reader in readers {
+        let mut sequence = Vec::new();
+        reader.read_to_end(&mut sequence)?;
+        sequences.push(sequence);
+    }
+
+    let owned_tree = SuffixTree::from_sequences_owned(sequences, alphabet);
+    let tree = owned_tree.tree();
+    let result = tree.longest_common_subsequence().next().map(|(seq_id, start, end)| {
+        tree.sequence_by_id(seq_id)[start..end].to_vec()
+    });
+
+    Ok(result)
+}
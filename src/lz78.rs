@@ -0,0 +1,70 @@
+use alloc::vec::Vec;
+#[cfg(not(feature = "std"))]
+use hashbrown::HashMap;
+#[cfg(feature = "std")]
+use std::collections::HashMap;
+use {NodeId, SequenceId, SuffixTree};
+
+impl<'a, 'b> SuffixTree<'a, 'b> {
+    /// Returns the LZ78 parse of the given sequence as pairs of a phrase index
+    /// and the byte extending that phrase. Phrases are numbered from 1 in the
+    /// order they are created, index 0 stands for the empty phrase, so phrase
+    /// `i` is phrase `pairs[i - 1].0` followed by `pairs[i - 1].1`.
+    ///
+    /// Each phrase is the longest phrase seen so far that prefixes the rest of
+    /// the sequence, extended by one byte. Finding it is the same walk from the
+    /// root that computes matching statistics, except that it stops at the
+    /// deepest locus belonging to a phrase instead of the deepest locus in the
+    /// tree. As every phrase is a substring of the sequence, the dictionary is
+    /// kept as a set of loci in the suffix tree instead of a trie of its own.
+    ///
+    /// If the sequence ends within an existing phrase, the last pair repeats
+    /// the pair of that phrase, so that the parse still decodes to the whole
+    /// sequence.
+    ///
+    /// Panics for trees built with a maximum depth.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"abababa", None);
+    /// assert_eq!(tree.lz78_parse(0), vec![(0, b'a'), (0, b'b'), (1, b'b'), (3, b'a')]);
+    /// ```
+    pub fn lz78_parse(&self, seq_id: SequenceId) -> Vec<(usize, u8)> {
+        assert!(self.max_depth.is_none(), "A truncated suffix tree cannot parse its sequences.");
+
+        let data = self.sequences[seq_id].data;
+        let mut phrases: HashMap<(NodeId, usize), usize> = HashMap::new();
+        let mut pairs: Vec<(usize, u8)> = Vec::new();
+
+        let mut position = 0;
+        while position < data.len() {
+            let mut cursor = self.walk(&[]).unwrap();
+            let mut phrase = 0;
+
+            loop {
+                let byte = data[position];
+                position += 1;
+
+                let stepped = cursor.step(byte);
+                debug_assert!(stepped, "Substrings of a sequence are always contained.");
+
+                match phrases.get(&(cursor.node(), cursor.offset())) {
+                    Some(&next_phrase) if position < data.len() => phrase = next_phrase,
+                    Some(&next_phrase) => {
+                        pairs.push(pairs[next_phrase - 1]);
+                        break;
+                    },
+                    None => {
+                        pairs.push((phrase, byte));
+                        phrases.insert((cursor.node(), cursor.offset()), pairs.len());
+                        break;
+                    },
+                }
+            }
+        }
+
+        pairs
+    }
+}
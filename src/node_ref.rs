@@ -0,0 +1,131 @@
+use {Node, NodeId, SequenceId, SuffixTree};
+
+/// The type of a node in a suffix tree.
+#[derive(Debug, Copy, Clone, Hash, Eq, PartialEq)]
+pub enum NodeKind {
+    Root,
+    Internal,
+    Leaf,
+}
+
+/// A reference to a node of a suffix tree whose id is known to be valid.
+///
+/// Unlike the accessors on `SuffixTree` taking a bare `NodeId`, none of the
+/// methods of a `NodeRef` panic, as the id has been checked once when the
+/// reference was created with `SuffixTree::get_node`.
+#[derive(Copy, Clone)]
+pub struct NodeRef<'t, 'a: 't, 'b: 't> {
+    tree: &'t SuffixTree<'a, 'b>,
+    id: NodeId,
+}
+
+impl<'t, 'a, 'b> NodeRef<'t, 'a, 'b> {
+    /// Returns the id of the node.
+    pub fn id(&self) -> NodeId {
+        self.id
+    }
+
+    /// Returns the type of the node.
+    pub fn kind(&self) -> NodeKind {
+        match self.tree.nodes[self.id] {
+            Node::Root(_) => NodeKind::Root,
+            Node::Internal(_) => NodeKind::Internal,
+            Node::Leaf(_) => NodeKind::Leaf,
+        }
+    }
+
+    /// Returns the parent of the node, or `None` for the root.
+    pub fn parent(&self) -> Option<NodeRef<'t, 'a, 'b>> {
+        self.tree.parent(self.id).map(|id| NodeRef { tree: self.tree, id })
+    }
+
+    /// Returns the children of the node in the same order as
+    /// `SuffixTree::children`.
+    pub fn children(&self) -> impl Iterator<Item = NodeRef<'t, 'a, 'b>> + 't {
+        let tree = self.tree;
+        tree.children(self.id).map(move |id| NodeRef { tree, id })
+    }
+
+    /// Returns the label of the edge leading into the node, without the
+    /// terminal symbol.
+    pub fn edge_label(&self) -> &'a [u8] {
+        self.tree.edge_label(self.id)
+    }
+
+    /// Returns the length of the path from the root to the node, counting the
+    /// terminal symbol at the end of a leaf. The depths are computed by
+    /// `SuffixTreeBuilder::build`, so this is `None` for the tree of a builder.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let builder = SuffixTree::builder_from_sequence(b"abab", None);
+    /// let node = builder.tree().walk(b"ab").unwrap().node();
+    /// assert_eq!(builder.tree().get_node(node).unwrap().string_depth(), None);
+    ///
+    /// let tree = builder.build();
+    /// assert_eq!(tree.get_node(node).unwrap().string_depth(), Some(2));
+    /// ```
+    pub fn string_depth(&self) -> Option<usize> {
+        self.tree.string_depths.get(self.id).cloned()
+    }
+
+    /// Returns the sequence id and the suffix spelled out by the path to the
+    /// node if it is a leaf, or `None` otherwise. Like `string_depth`, this is
+    /// also `None` for the tree of a builder.
+    pub fn leaf_suffix(&self) -> Option<(SequenceId, &'a [u8])> {
+        match self.kind() {
+            NodeKind::Leaf if self.string_depth().is_some() => Some(self.tree.leaf_suffix(self.id)),
+            _ => None,
+        }
+    }
+}
+
+impl<'a, 'b> SuffixTree<'a, 'b> {
+    /// Returns the type of the node with the given id, or `None` if the tree
+    /// has no such node.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::{NodeKind, SuffixTree};
+    ///
+    /// let tree = SuffixTree::from_sequence(b"abab", None);
+    /// assert_eq!(tree.node_kind(0), Some(NodeKind::Root));
+    ///
+    /// let node = tree.walk(b"ab").unwrap().node();
+    /// assert_eq!(tree.node_kind(node), Some(NodeKind::Internal));
+    /// assert_eq!(tree.node_kind(1000), None);
+    /// ```
+    pub fn node_kind(&self, id: NodeId) -> Option<NodeKind> {
+        self.get_node(id).map(|node| node.kind())
+    }
+
+    /// Returns a reference to the node with the given id, or `None` if the
+    /// tree has no such node. This is meant for traversals working with ids
+    /// that come from outside, which can then be handled without panicking.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::{NodeKind, SuffixTree};
+    ///
+    /// let tree = SuffixTree::from_sequence(b"abab", None);
+    /// let root = tree.get_node(0).unwrap();
+    ///
+    /// let leaves: Vec<&[u8]> = root.children()
+    ///     .flat_map(|child| child.children())
+    ///     .filter_map(|node| node.leaf_suffix())
+    ///     .map(|(_, suffix)| suffix)
+    ///     .collect();
+    /// assert_eq!(leaves, vec![&b"ab"[..], b"abab", b"b", b"bab"]);
+    ///
+    /// assert!(tree.get_node(1000).is_none());
+    /// ```
+    pub fn get_node<'s>(&'s self, id: NodeId) -> Option<NodeRef<'s, 'a, 'b>> {
+        if id < self.nodes.len() {
+            Some(NodeRef { tree: self, id })
+        } else {
+            None
+        }
+    }
+}
@@ -0,0 +1,179 @@
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use alphabet::Alphabet;
+#[cfg(feature = "std")]
+use std::io::{self, Read};
+use {SuffixTree, SuffixTreeBuilder};
+
+/// A suffix tree that owns the sequences it was built from.
+pub struct OwnedSuffixTree<'b> {
+    // The tree borrows from `sequences`, so it is declared first in order to be
+    // dropped first. It must never be handed out with the `'static` lifetime.
+    tree: SuffixTree<'static, 'b>,
+    sequences: Vec<Box<[u8]>>,
+    // The reversed copies of the sequences, empty unless the tree is reversed.
+    reversed_sequences: Vec<Box<[u8]>>,
+}
+
+impl<'b> OwnedSuffixTree<'b> {
+    // The sequences are given in their original form and stored the way the
+    // settings of the builder expect them.
+    fn new(sequences: Vec<Vec<u8>>, mut tree_builder: SuffixTreeBuilder<'static, 'b>)
+        -> OwnedSuffixTree<'b>
+    {
+        // The boxed slices live on the heap, so their address stays the same
+        // when they are moved into the struct below, and they are neither
+        // modified nor dropped before the tree.
+        fn extend_lifetime(sequence: &[u8]) -> &'static [u8] {
+            unsafe { &*(sequence as *const [u8]) }
+        }
+
+        let reversed = tree_builder.tree.is_reversed();
+        let circular = tree_builder.tree.is_circular();
+        let sequences: Vec<Box<[u8]>> = sequences.into_iter().map(|mut sequence| {
+            if circular {
+                sequence.extend_from_within(..);
+            }
+
+            sequence.into_boxed_slice()
+        }).collect();
+
+        let mut reversed_sequences = Vec::new();
+        if reversed {
+            reversed_sequences = sequences.iter().map(|sequence| {
+                sequence.iter().rev().cloned().collect::<Vec<u8>>().into_boxed_slice()
+            }).collect();
+
+            for (sequence, reversed) in sequences.iter().zip(&reversed_sequences) {
+                tree_builder.add_reversed_sequence(extend_lifetime(sequence), extend_lifetime(reversed));
+            }
+        } else {
+            for sequence in &sequences {
+                tree_builder.add_sequence(extend_lifetime(sequence));
+            }
+        }
+
+        OwnedSuffixTree {
+            tree: tree_builder.build(),
+            sequences,
+            reversed_sequences,
+        }
+    }
+
+    /// Returns the suffix tree, borrowing the sequences from `self`.
+    pub fn tree<'s>(&'s self) -> &'s SuffixTree<'s, 'b> {
+        &self.tree
+    }
+
+    /// Drops the tree and returns the sequences it was built from, in their
+    /// original orientation and without doubling circular sequences.
+    pub fn into_sequences(self) -> Vec<Vec<u8>> {
+        let OwnedSuffixTree { tree, sequences, reversed_sequences } = self;
+        let circular = tree.is_circular();
+        drop(tree);
+        drop(reversed_sequences);
+
+        sequences.into_iter().map(|sequence| {
+            let mut sequence = sequence.into_vec();
+            if circular {
+                let length = sequence.len() / 2;
+                sequence.truncate(length);
+            }
+
+            sequence
+        }).collect()
+    }
+}
+
+impl<'b> SuffixTree<'static, 'b> {
+    /// Builds a suffix tree that takes ownership of the given sequences, so
+    /// that the caller does not have to keep them alive separately.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let sequences = vec![b"test".to_vec(), b"rest".to_vec()];
+    /// let owned = SuffixTree::from_sequences_owned(sequences, None);
+    ///
+    /// assert_eq!(owned.tree().sequences_containing(b"est"), vec![0, 1]);
+    /// ```
+    pub fn from_sequences_owned(sequences: Vec<Vec<u8>>, alphabet: Option<Alphabet<'b>>)
+        -> OwnedSuffixTree<'b>
+    {
+        OwnedSuffixTree::new(sequences, SuffixTreeBuilder::new(alphabet))
+    }
+
+    /// Builds a suffix tree over the reversed sequence, as described for
+    /// `SuffixTreeBuilder::with_reversed`. Suffixes of the reversed sequence
+    /// are prefixes of the original read backwards, so this answers questions
+    /// about what precedes a pattern, and positions are reported in the
+    /// original sequence, which is also what `sequence_by_id` returns.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let owned = SuffixTree::from_sequence_reversed(b"testing", None);
+    /// let tree = owned.tree();
+    ///
+    /// assert_eq!(tree.sequence_by_id(0), b"testing");
+    /// assert_eq!(tree.find(b"tse").collect::<Vec<_>>(), vec![(0, 1, 4)]);
+    /// assert_eq!(tree.find_matches(b"tse").next().unwrap().as_bytes(), b"est");
+    /// assert_eq!(tree.find_first(b"gn"), Some((0, 5, 7)));
+    /// assert_eq!(owned.into_sequences(), vec![b"testing".to_vec()]);
+    /// ```
+    pub fn from_sequence_reversed(sequence: &[u8], alphabet: Option<Alphabet<'b>>)
+        -> OwnedSuffixTree<'b>
+    {
+        let tree_builder = SuffixTreeBuilder::new(alphabet).with_reversed(true);
+        OwnedSuffixTree::new(vec![sequence.to_vec()], tree_builder)
+    }
+
+    /// Builds a suffix tree over a circular sequence, such as a plasmid or a
+    /// bacterial genome, by indexing two copies of it in a row, as described
+    /// for `SuffixTreeBuilder::with_circular`. Use `find_circular` to find
+    /// patterns that may wrap around the end of the sequence.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let owned = SuffixTree::from_circular(b"TACGGA", None);
+    ///
+    /// assert_eq!(owned.tree().find_circular(b"GATA"), vec![(0, 4, 8)]);
+    /// assert_eq!(owned.into_sequences(), vec![b"TACGGA".to_vec()]);
+    /// ```
+    pub fn from_circular(sequence: &[u8], alphabet: Option<Alphabet<'b>>) -> OwnedSuffixTree<'b> {
+        let tree_builder = SuffixTreeBuilder::new(alphabet).with_circular(true);
+        OwnedSuffixTree::new(vec![sequence.to_vec()], tree_builder)
+    }
+}
+
+impl SuffixTree<'static, 'static> {
+    /// Reads the reader to its end and builds a suffix tree over its contents,
+    /// with the smallest alphabet that contains every byte read, see
+    /// `Alphabet::infer`. Fails with `io::ErrorKind::InvalidData` if byte 255
+    /// was read.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let owned = SuffixTree::from_reader(&b"the cat sat on the mat"[..]).unwrap();
+    /// let tree = owned.tree();
+    ///
+    /// assert_eq!(tree.find(b"at ").count(), 2);
+    /// assert_eq!(tree.find(b"the").count(), 2);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: Read>(mut reader: R) -> io::Result<OwnedSuffixTree<'static>> {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data)?;
+
+        let alphabet = Alphabet::infer(&data).map_err(|error| {
+            io::Error::new(io::ErrorKind::InvalidData, error)
+        })?;
+        Ok(OwnedSuffixTree::new(vec![data], SuffixTreeBuilder::new(Some(alphabet))))
+    }
+}
@@ -0,0 +1,75 @@
+use alloc::vec::Vec;
+use core::fmt;
+use {SequenceId, SuffixTree};
+
+/// The number of low bits holding the start position in occurences packed by
+/// `SuffixTree::find_packed`. The remaining 24 high bits hold the sequence id,
+/// so positions have to be below 2^40 and ids below 2^24.
+pub const PACKED_START_BITS: u32 = 40;
+
+const START_MASK: u64 = (1 << PACKED_START_BITS) - 1;
+
+/// An occurence does not fit the layout of `SuffixTree::find_packed`.
+#[derive(Debug, Copy, Clone, Eq, PartialEq)]
+pub struct PackingError {
+    pub seq_id: SequenceId,
+    pub start: usize,
+}
+
+impl fmt::Display for PackingError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "occurence at position {} of sequence {} does not fit into 64 bits",
+            self.start, self.seq_id
+        )
+    }
+}
+
+#[cfg(feature = "std")]
+impl ::std::error::Error for PackingError {}
+
+/// Returns the sequence id and start position of an occurence packed by
+/// `SuffixTree::find_packed`.
+pub fn unpack_occurence(packed: u64) -> (SequenceId, usize) {
+    ((packed >> PACKED_START_BITS) as SequenceId, (packed & START_MASK) as usize)
+}
+
+impl<'a, 'b> SuffixTree<'a, 'b> {
+    /// Returns the occurences of the given pattern like `find`, but packs each
+    /// of them into a single `u64`, with the sequence id in the high 24 bits
+    /// and the start position in the low `PACKED_START_BITS` bits. The end is
+    /// the start plus the length of the pattern and is not stored. This takes
+    /// a third of the memory of the tuples returned by `find`, and the layout
+    /// is easy to read from other languages.
+    ///
+    /// Returns an error for the first occurence whose sequence id or start
+    /// position does not fit into its bits.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::{unpack_occurence, SuffixTree};
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"abab", b"cab"], None);
+    /// let mut occurences = tree.find_packed(b"ab").unwrap();
+    /// occurences.sort();
+    ///
+    /// assert_eq!(occurences, vec![0, 2, 1 << 40 | 1]);
+    /// assert_eq!(unpack_occurence(occurences[2]), (1, 1));
+    /// ```
+    pub fn find_packed(&self, pattern: &[u8]) -> Result<Vec<u64>, PackingError> {
+        let occurences = self.find(pattern);
+        let mut packed = Vec::with_capacity(occurences.len());
+
+        for (seq_id, start, _) in occurences {
+            let seq_id_fits = (seq_id as u64) < 1 << (64 - PACKED_START_BITS);
+            if !seq_id_fits || start as u64 > START_MASK {
+                return Err(PackingError { seq_id, start });
+            }
+
+            packed.push((seq_id as u64) << PACKED_START_BITS | start as u64);
+        }
+
+        Ok(packed)
+    }
+}
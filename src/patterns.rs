@@ -0,0 +1,77 @@
+use alloc::vec::Vec;
+use alphabet::{Alphabet, AlphabetError};
+use {SequenceId, SuffixTree};
+
+/// A set of patterns that has been checked against an alphabet once, so that
+/// it can be searched for in many suffix trees over that alphabet.
+///
+/// Every symbol of the patterns is replaced by its rank up front, so walking
+/// down a tree looks up children by rank without consulting the alphabet.
+pub struct PreparedPatterns {
+    patterns: Vec<Vec<u8>>,
+    ranks: Vec<Vec<u8>>,
+    // The distinct symbols of the patterns with their rank, sorted by symbol.
+    symbol_ranks: Vec<(u8, u8)>,
+}
+
+impl PreparedPatterns {
+    /// Copies the patterns and looks up the rank of their symbols, or returns
+    /// an error for the first symbol that is not part of the alphabet.
+    pub fn new(patterns: &[&[u8]], alphabet: &Alphabet) -> Result<PreparedPatterns, AlphabetError> {
+        let ranks = patterns.iter().map(|pattern| {
+            pattern.iter().map(|&symbol| {
+                alphabet.try_rank_of_symbol(symbol).ok_or(AlphabetError::UnknownSymbol(symbol))
+            }).collect::<Result<Vec<u8>, AlphabetError>>()
+        }).collect::<Result<Vec<Vec<u8>>, AlphabetError>>()?;
+
+        let mut symbol_ranks: Vec<(u8, u8)> = patterns.iter().zip(&ranks).flat_map(|(pattern, ranks)| {
+            pattern.iter().cloned().zip(ranks.iter().cloned())
+        }).collect();
+        symbol_ranks.sort();
+        symbol_ranks.dedup();
+
+        Ok(PreparedPatterns {
+            patterns: patterns.iter().map(|pattern| pattern.to_vec()).collect(),
+            ranks,
+            symbol_ranks,
+        })
+    }
+
+    pub fn len(&self) -> usize {
+        self.patterns.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// Returns the occurences of every pattern in the given tree, in the order
+    /// in which the patterns were passed to `new`.
+    ///
+    /// Panics if the alphabet of the tree ranks a symbol of the patterns
+    /// differently than the alphabet they were prepared with.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::{PreparedPatterns, SuffixTree};
+    /// use suffix_tree::alphabet::Alphabet;
+    ///
+    /// let alphabet = Alphabet::new(b"ACGT");
+    /// let patterns = PreparedPatterns::new(&[b"GA", b"TT"], &alphabet).unwrap();
+    ///
+    /// let tree = SuffixTree::from_sequence(b"GATTACA", Some(alphabet.clone()));
+    /// assert_eq!(patterns.matches_in(&tree), vec![vec![(0, 0, 2)], vec![(0, 2, 4)]]);
+    /// ```
+    pub fn matches_in(&self, tree: &SuffixTree) -> Vec<Vec<(SequenceId, usize, usize)>> {
+        for &(symbol, rank) in &self.symbol_ranks {
+            assert_eq!(
+                tree.alphabet.try_rank_of_symbol(symbol), Some(rank),
+                "The patterns were prepared with a different rank for symbol {:?}.", symbol as char
+            );
+        }
+
+        self.patterns.iter().zip(&self.ranks).map(|(pattern, ranks)| {
+            tree.find_ranked(pattern, ranks).collect()
+        }).collect()
+    }
+}
@@ -0,0 +1,85 @@
+use alloc::rc::Rc;
+use alloc::vec::Vec;
+use core::cell::RefCell;
+
+use {NodeId, Occurences, SequenceId, SuffixTree};
+
+type Positions = Rc<Vec<(SequenceId, usize)>>;
+
+/// Remembers the leaves below recently queried nodes, so that repeated `find`
+/// calls for patterns sharing a prefix do not traverse the same subtree again.
+///
+/// The cache is a separate wrapper so that `SuffixTree` itself stays `Sync`.
+/// It trades memory for speed: every cached node holds a copy of all the
+/// occurences below it, which for nodes close to the root can be a large part
+/// of the tree. Keep `capacity` small, and only use it when queries repeat.
+///
+/// #Examples
+/// ```
+/// use suffix_tree::{QueryCache, SuffixTree};
+///
+/// let tree = SuffixTree::from_sequences(&[b"tester", b"estes"], None);
+/// let cache = QueryCache::new(&tree, 16);
+///
+/// for pattern in &[&b"est"[..], b"este", b"ester"] {
+///     assert_eq!(cache.find(pattern).len(), tree.find(pattern).len());
+/// }
+/// ```
+pub struct QueryCache<'t, 'a: 't, 'b: 't> {
+    tree: &'t SuffixTree<'a, 'b>,
+    capacity: usize,
+    // Ordered from least to most recently used.
+    entries: RefCell<Vec<(NodeId, Positions)>>,
+}
+
+impl<'t, 'a, 'b> QueryCache<'t, 'a, 'b> {
+    /// Creates a cache holding the occurences of at most `capacity` nodes.
+    pub fn new(tree: &'t SuffixTree<'a, 'b>, capacity: usize) -> QueryCache<'t, 'a, 'b> {
+        QueryCache { tree, capacity, entries: RefCell::new(Vec::new()) }
+    }
+
+    pub fn tree(&self) -> &'t SuffixTree<'a, 'b> {
+        self.tree
+    }
+
+    /// Forgets all cached nodes.
+    pub fn clear(&self) {
+        self.entries.borrow_mut().clear();
+    }
+
+    /// Returns all the occurences of the given pattern, like `SuffixTree::find`.
+    pub fn find(&self, pattern: &[u8]) -> Occurences<'t> {
+        let cursor = match self.tree.walk(pattern) {
+            Some(cursor) => cursor,
+            None => return Occurences::empty(),
+        };
+
+        if cursor.node() == 0 {
+            return Occurences::empty();
+        }
+
+        let positions = self.node_occurences(cursor.node());
+        Occurences::from_positions(self.tree, positions, cursor.offset(), cursor.depth())
+    }
+
+    fn node_occurences(&self, node: NodeId) -> Positions {
+        let mut entries = self.entries.borrow_mut();
+
+        if let Some(position) = entries.iter().position(|&(id, _)| id == node) {
+            let entry = entries.remove(position);
+            let positions = entry.1.clone();
+            entries.push(entry);
+            return positions;
+        }
+
+        let positions = Rc::new(self.tree.node_occurences(node, 0).collect::<Vec<_>>());
+        if self.capacity > 0 {
+            if entries.len() == self.capacity {
+                entries.remove(0);
+            }
+            entries.push((node, positions.clone()));
+        }
+
+        positions
+    }
+}
@@ -0,0 +1,235 @@
+use alloc::vec::Vec;
+use {match_start, LeafNode, Node, NodeId, SequenceId, SuffixTree, Symbol};
+
+impl<'a, 'b> SuffixTree<'a, 'b> {
+    /// Returns every occurence of one of the sequences of the tree in the
+    /// given text, as the id of the sequence and its start and end position in
+    /// the text, ordered by end position and sequence id. This is meant for
+    /// trees built over a set of patterns, which are then all searched for in
+    /// a single pass over the text, like with Aho-Corasick.
+    ///
+    /// For each start position the longest prefix of the rest of the text that
+    /// is contained in the tree is matched, following suffix links to get from
+    /// one start position to the next (the matching statistics of the text).
+    /// The patterns occuring at a start position are exactly those ending on
+    /// the path to that match, which are found by following links between
+    /// nodes at which patterns end. The time taken is linear in the size of
+    /// the tree, the length of the text and the number of occurences. Empty
+    /// sequences are never reported.
+    ///
+    /// Panics for trees built with a maximum depth.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"he", b"she", b"his", b"hers"], None);
+    /// assert_eq!(tree.scan(b"ushers"), vec![(0, 2, 4), (1, 1, 4), (3, 2, 6)]);
+    /// ```
+    pub fn scan(&self, text: &[u8]) -> Vec<(SequenceId, usize, usize)> {
+        assert!(self.max_depth.is_none(), "A truncated suffix tree cannot be used for scanning.");
+
+        let scanner = Scanner::new(self);
+        let mut occurences = Vec::new();
+        self.matching_statistics(text, |start, node, depth| {
+            scanner.report(node, depth, |seq_id, length| {
+                occurences.push((seq_id, start, start + length));
+            });
+        });
+
+        occurences.sort_unstable_by_key(|&(seq_id, _, end)| (end, seq_id));
+        occurences
+    }
+
+    /// Returns an occurence of the longest substring of the query that is
+    /// contained in the tree, as the id of the sequence and the start and end
+    /// position in that sequence. If several substrings are equally long, the
+    /// one occuring first in the query is taken. Returns `None` if no byte of
+    /// the query is contained in the tree.
+    ///
+    /// The matches are the matching statistics of the query, as described for
+    /// `scan`, so this takes time linear in the length of the query.
+    ///
+    /// Panics for trees built with a maximum depth.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequences(&[b"GATTACA", b"TACCAGT"], None);
+    ///
+    /// let (seq_id, start, end) = tree.longest_match(b"CCTACCG").unwrap();
+    /// assert_eq!((seq_id, start, end), (1, 0, 4));
+    /// assert_eq!(&tree.sequence_by_id(seq_id)[start..end], b"TACC");
+    ///
+    /// assert_eq!(tree.longest_match(b"XYZ"), None);
+    /// ```
+    pub fn longest_match(&self, query: &[u8]) -> Option<(SequenceId, usize, usize)> {
+        assert!(self.max_depth.is_none(), "A truncated suffix tree cannot compute matching statistics.");
+
+        let mut longest: Option<(NodeId, usize)> = None;
+        self.matching_statistics(query, |_, node, depth| {
+            if depth > longest.map_or(0, |(_, longest_depth)| longest_depth) {
+                longest = Some((node, depth));
+            }
+        });
+
+        let (node, depth) = longest?;
+        let (seq_id, position) = self.node_occurences(node, 0).next().unwrap();
+        let end = position + depth - self.parent_depth(node);
+        Some(self.original_range(seq_id, match_start(seq_id, end, depth), end))
+    }
+
+    /// Calls `f` with every start position in the text along with the longest
+    /// match starting there, given as the node on whose incoming edge the
+    /// match ends and its length.
+    fn matching_statistics<F: FnMut(usize, NodeId, usize)>(&self, text: &[u8], mut f: F) {
+        let mut node = 0;
+        let mut depth = 0;
+        for start in 0..text.len() {
+            while start + depth < text.len() {
+                match self.extend_match(node, depth, text[start + depth]) {
+                    Some(next_node) => {
+                        node = next_node;
+                        depth += 1;
+                    },
+                    None => break,
+                }
+            }
+
+            f(start, node, depth);
+
+            if depth > 0 {
+                let (next_node, next_depth) = self.shorten_match(node, depth, &text[start + 1..]);
+                node = next_node;
+                depth = next_depth;
+            }
+        }
+    }
+
+    // The string depth of the end of the edge leading to the node, which for
+    // leaves does not count the terminal symbol.
+    fn end_depth(&self, node: NodeId) -> usize {
+        match self.nodes[node] {
+            Node::Leaf(_) => self.string_depths[node] - 1,
+            _ => self.string_depths[node],
+        }
+    }
+
+    fn parent_depth(&self, node: NodeId) -> usize {
+        self.parent(node).map_or(0, |parent| self.string_depths[parent])
+    }
+
+    // Extends the match at `depth` on the edge leading to `node` by one byte,
+    // returning the node whose edge the extended match ends on.
+    fn extend_match(&self, node: NodeId, depth: usize, byte: u8) -> Option<NodeId> {
+        let end_depth = self.end_depth(node);
+        if depth < end_depth {
+            let offset = depth - self.parent_depth(node);
+            if self.edge_label(node)[offset] == byte { Some(node) } else { None }
+        } else if self.nodes[node].is_leaf() {
+            None
+        } else {
+            self.get_child(node, Symbol::Regular(byte))
+        }
+    }
+
+    // Moves from the match of length `depth` ending on the edge leading to
+    // `node` to the match without its first byte, where `rest` is the text
+    // following that first byte.
+    fn shorten_match(&self, node: NodeId, depth: usize, rest: &[u8]) -> (NodeId, usize) {
+        let last_node = if depth == self.end_depth(node) && !self.nodes[node].is_leaf() {
+            node
+        } else {
+            self.parent(node).unwrap()
+        };
+
+        let (mut current, mut current_depth) = match self.suffix_link(last_node) {
+            Some(link) if last_node != 0 => (link, self.string_depths[last_node] - 1),
+            _ => (0, 0),
+        };
+
+        // Every byte up to the target depth is known to match, so only the
+        // first byte of each edge has to be looked at.
+        let target_depth = depth - 1;
+        while current_depth < target_depth {
+            current = self.get_child(current, Symbol::Regular(rest[current_depth])).unwrap();
+            current_depth = self.end_depth(current).min(target_depth);
+        }
+
+        (current, target_depth)
+    }
+}
+
+// Whether the node is the leaf of the suffix starting at the beginning of its
+// sequence.
+fn is_whole_sequence(tree: &SuffixTree, node: NodeId) -> bool {
+    match tree.nodes[node] {
+        Node::Leaf(LeafNode { seq_id, .. }) => tree.string_depths[node] == tree.sequences[seq_id].len(),
+        _ => false,
+    }
+}
+
+struct Scanner<'t, 'a: 't, 'b: 't> {
+    tree: &'t SuffixTree<'a, 'b>,
+    // The ids of the sequences ending at each internal node.
+    pattern_ids: Vec<Vec<SequenceId>>,
+    // The closest node on the path from the root to each node, including the
+    // node itself, at which a sequence ends.
+    pattern_links: Vec<Option<NodeId>>,
+}
+
+impl<'t, 'a, 'b> Scanner<'t, 'a, 'b> {
+    fn new(tree: &'t SuffixTree<'a, 'b>) -> Scanner<'t, 'a, 'b> {
+        let mut pattern_ids = vec![Vec::new(); tree.nodes.len()];
+        for (node, pattern_ids) in pattern_ids.iter_mut().enumerate().skip(1) {
+            for child in tree.children(node) {
+                if tree.edge_len(child) == 0 && is_whole_sequence(tree, child) {
+                    pattern_ids.push(tree.leaf_suffix(child).0);
+                }
+            }
+        }
+
+        let mut pattern_links = vec![None; tree.nodes.len()];
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            for child in tree.children(node) {
+                pattern_links[child] = if pattern_ids[child].is_empty() {
+                    pattern_links[node]
+                } else {
+                    Some(child)
+                };
+                stack.push(child);
+            }
+        }
+
+        Scanner { tree, pattern_ids, pattern_links }
+    }
+
+    // Calls `f` with the id and length of every sequence that is a prefix of
+    // the match of length `depth` ending on the edge leading to `node`.
+    fn report<F: FnMut(SequenceId, usize)>(&self, node: NodeId, depth: usize, mut f: F) {
+        if depth == 0 {
+            return;
+        }
+
+        let end_depth = self.tree.end_depth(node);
+        let mut current = if depth == end_depth {
+            if is_whole_sequence(self.tree, node) {
+                f(self.tree.leaf_suffix(node).0, depth);
+            }
+
+            self.pattern_links[node]
+        } else {
+            self.tree.parent(node).and_then(|parent| self.pattern_links[parent])
+        };
+
+        while let Some(pattern_node) = current {
+            for &seq_id in &self.pattern_ids[pattern_node] {
+                f(seq_id, self.tree.string_depths[pattern_node]);
+            }
+
+            current = self.tree.parent(pattern_node).and_then(|parent| self.pattern_links[parent]);
+        }
+    }
+}
@@ -0,0 +1,153 @@
+use alloc::collections::BTreeMap;
+use alloc::vec::Vec;
+use {SequenceId, SuffixTree};
+
+impl<'a, 'b> SuffixTree<'a, 'b> {
+    /// Returns the maximal tandem arrays of the given sequence as pairs of
+    /// start position and period, sorted by start and period.
+    ///
+    /// A tandem array is a substring consisting of at least two consecutive
+    /// copies of a primitive word, the period being the length of that word.
+    /// It is maximal if no further copy of the word directly precedes or
+    /// follows it, so its number of copies follows from its start and period.
+    ///
+    /// The arrays are derived from the maximal runs of the sequence. For each
+    /// period `p`, every run of period `p` spans two positions `q` and `q + p`
+    /// with `q` a multiple of `p`, and is found by extending the match between
+    /// them forwards and backwards. These extensions are longest common
+    /// extension queries, answered in constant time from the suffix arrays of
+    /// the sequence and of its reverse, which are read off suffix trees. This
+    /// takes `O(n log n)` time for a sequence of length `n`.
+    ///
+    /// #Examples
+    /// ```
+    /// use suffix_tree::SuffixTree;
+    ///
+    /// let tree = SuffixTree::from_sequence(b"xabababay", None);
+    /// // "ababab" and "bababa" with period 2.
+    /// assert_eq!(tree.tandem_repeats(0), vec![(1, 2), (2, 2)]);
+    ///
+    /// let tree = SuffixTree::from_sequence(b"aabaab", None);
+    /// assert_eq!(tree.tandem_repeats(0), vec![(0, 1), (0, 3), (3, 1)]);
+    /// ```
+    pub fn tandem_repeats(&self, seq_id: SequenceId) -> Vec<(usize, usize)> {
+        let data = self.sequences[seq_id].data;
+        let length = data.len();
+
+        let suffix_array: Vec<usize> = self.root_node().children.iter()
+            .flat_map(|child| self.node_occurences(child, 0))
+            .filter(|&(id, _)| id == seq_id)
+            .map(|(_, start)| start)
+            .collect();
+        let forward = LongestCommonExtension::new(data, suffix_array);
+
+        let reversed: Vec<u8> = data.iter().rev().cloned().collect();
+        let reversed_tree = SuffixTree::from_sequences_owned(vec![reversed], Some(self.alphabet.clone()));
+        let reversed_tree = reversed_tree.tree();
+        let backward = LongestCommonExtension::new(
+            reversed_tree.sequence_by_id(0),
+            reversed_tree.leaves_by_sequence().remove(0)
+        );
+
+        // Maps the bounds of each run to its smallest period, which is the
+        // primitive one, as a run has the same bounds for all of its periods.
+        let mut runs: BTreeMap<(usize, usize), usize> = BTreeMap::new();
+        for period in 1..=length / 2 {
+            for q in (0..length - period).step_by(period) {
+                let after = forward.lce(q, q + period);
+                // The common extension to the left of `q` and `q + period`,
+                // which in the reversed sequence is to the right.
+                let before = if q == 0 {
+                    0
+                } else {
+                    backward.lce(length - q, length - q - period)
+                };
+
+                if before + after >= period {
+                    let run = (q - before, q + period + after);
+                    let smallest = runs.entry(run).or_insert(period);
+                    *smallest = (*smallest).min(period);
+                }
+            }
+        }
+
+        // A run of period `p` contains one maximal tandem array for each of
+        // its first `p` positions that is followed by at least two periods.
+        let mut tandem_repeats = Vec::new();
+        for (&(start, end), &period) in &runs {
+            let count = period.min(end - start - 2 * period + 1);
+            tandem_repeats.extend((start..start + count).map(|start| (start, period)));
+        }
+
+        tandem_repeats.sort_unstable();
+        tandem_repeats
+    }
+}
+
+/// Answers longest common extension queries on a sequence with a sparse table
+/// over the longest common prefixes of adjacent suffixes in its suffix array.
+struct LongestCommonExtension {
+    length: usize,
+    ranks: Vec<usize>,
+    // `lcps[k][r]` is the minimum of the longest common prefixes at ranks
+    // `r..r + 2^k`.
+    lcps: Vec<Vec<usize>>,
+}
+
+impl LongestCommonExtension {
+    /// Creates the table from the suffix array including the empty suffix.
+    fn new(data: &[u8], suffix_array: Vec<usize>) -> LongestCommonExtension {
+        let length = data.len();
+        let mut ranks = vec![0; length + 1];
+        for (rank, &start) in suffix_array.iter().enumerate() {
+            ranks[start] = rank;
+        }
+
+        // Kasai's algorithm, `lcp[r]` is the longest common prefix of the
+        // suffixes at rank `r - 1` and `r`.
+        let mut lcp = vec![0; length + 1];
+        let mut common = 0;
+        for start in 0..length {
+            let rank = ranks[start];
+            let previous = suffix_array[rank - 1];
+            while start + common < length && previous + common < length
+                && data[start + common] == data[previous + common]
+            {
+                common += 1;
+            }
+
+            lcp[rank] = common;
+            common = common.saturating_sub(1);
+        }
+
+        let mut lcps = vec![lcp];
+        let mut width = 1;
+        while 2 * width <= length + 1 {
+            let previous = lcps.last().unwrap();
+            let next = (0..=length + 1 - 2 * width)
+                .map(|rank| previous[rank].min(previous[rank + width]))
+                .collect();
+            lcps.push(next);
+            width *= 2;
+        }
+
+        LongestCommonExtension { length, ranks, lcps }
+    }
+
+    /// Returns the length of the longest common prefix of the suffixes
+    /// starting at `i` and `j`.
+    fn lce(&self, i: usize, j: usize) -> usize {
+        if i == j {
+            return self.length - i;
+        }
+
+        let (low, high) = if self.ranks[i] < self.ranks[j] {
+            (self.ranks[i] + 1, self.ranks[j])
+        } else {
+            (self.ranks[j] + 1, self.ranks[i])
+        };
+
+        let level = (usize::BITS - 1 - (high - low + 1).leading_zeros()) as usize;
+        self.lcps[level][low].min(self.lcps[level][high + 1 - (1 << level)])
+    }
+}
@@ -0,0 +1,118 @@
+use alloc::vec::Vec;
+use {ChildMap, ChildStorage, InternalNode, LeafNode, Node, NodeId, RootNode, SubtreeLeaves, SuffixTree, Symbol};
+
+impl<'a, 'b> SuffixTree<'a, 'b> {
+    /// Removes all internal nodes whose incoming edge starts at a string depth
+    /// of at least `max_depth`.
+    ///
+    /// Patterns of length at most `max_depth` end on an edge that starts above
+    /// that depth, so below the first node reaching it only the leaves matter.
+    /// The leaves of such a node are attached to it directly and their start is
+    /// moved up so that the path to them still spells out their suffix. The
+    /// leaf of a suffix ending at the node is keyed by its terminal as usual,
+    /// the others are kept apart from the children keyed by a symbol, as
+    /// several of them can start with the same one.
+    pub(crate) fn truncate(&mut self, max_depth: usize, child_storage: ChildStorage) {
+        struct Truncation<'t, 'a: 't, 'b: 't> {
+            tree: &'t SuffixTree<'a, 'b>,
+            max_depth: usize,
+            child_storage: ChildStorage,
+            nodes: Vec<Node>,
+            new_ids: Vec<Option<NodeId>>,
+        }
+
+        impl<'t, 'a, 'b> Truncation<'t, 'a, 'b> {
+            fn new_child_map(&self) -> ChildMap {
+                ChildMap::new(self.tree.alphabet.size, self.child_storage)
+            }
+
+            fn symbol_of(&self, node: NodeId) -> Symbol {
+                match self.tree.nodes[node] {
+                    Node::Leaf(LeafNode { seq_id, .. }) if self.tree.edge_label(node).is_empty() => {
+                        Symbol::Terminal(seq_id)
+                    },
+                    _ => Symbol::Regular(self.tree.edge_label(node)[0]),
+                }
+            }
+
+            fn copy_children(&mut self, node: NodeId, depth: usize, children: &mut ChildMap) {
+                let tree = self.tree;
+                let new_parent = self.new_ids[node].unwrap();
+                for child in tree.nodes[node].children().unwrap().iter() {
+                    let new_child = self.copy(child, depth);
+                    self.nodes[new_child].set_parent(new_parent);
+                    children.add_child(&tree.alphabet, self.symbol_of(child), new_child);
+                }
+            }
+
+            fn copy(&mut self, node: NodeId, depth: usize) -> NodeId {
+                let tree = self.tree;
+                let new_id = self.nodes.len();
+                self.new_ids[node] = Some(new_id);
+
+                let new_node = match tree.nodes[node] {
+                    Node::Root(_) => {
+                        self.nodes.push(Node::new_leaf(0, 0));
+                        let mut children = ChildMap::new(tree.alphabet.size, ChildStorage::Dense);
+                        self.copy_children(node, 0, &mut children);
+                        Node::Root(RootNode { children })
+                    },
+                    Node::Internal(InternalNode { seq_id, start, end, .. }) => {
+                        self.nodes.push(Node::new_leaf(0, 0));
+                        let depth = depth + end - start;
+                        let mut children = self.new_child_map();
+
+                        if depth >= self.max_depth {
+                            let leaves = SubtreeLeaves::new(tree, node, depth - (end - start));
+                            for (leaf_seq_id, suffix_start) in leaves {
+                                let leaf = self.nodes.len();
+                                let leaf_start = suffix_start + depth;
+                                let mut new_leaf = Node::new_leaf(leaf_seq_id, leaf_start);
+                                new_leaf.set_parent(new_id);
+                                self.nodes.push(new_leaf);
+
+                                if leaf_start == tree.sequences[leaf_seq_id].data.len() {
+                                    children.add_child(&tree.alphabet, Symbol::Terminal(leaf_seq_id), leaf);
+                                } else {
+                                    children.add_truncated_leaf(leaf);
+                                }
+                            }
+                        } else {
+                            self.copy_children(node, depth, &mut children);
+                        }
+
+                        Node::new_internal(children, seq_id, start, end)
+                    },
+                    Node::Leaf(LeafNode { seq_id, start, .. }) => {
+                        self.nodes.push(Node::new_leaf(0, 0));
+                        Node::new_leaf(seq_id, start)
+                    },
+                };
+
+                self.nodes[new_id] = new_node;
+                new_id
+            }
+        }
+
+        let mut truncation = Truncation {
+            tree: self,
+            max_depth,
+            child_storage,
+            nodes: Vec::new(),
+            new_ids: vec![None; self.nodes.len()],
+        };
+        truncation.copy(0, 0);
+
+        let Truncation { nodes, new_ids, .. } = truncation;
+        let mut suffix_links = vec![None; nodes.len()];
+        for (node, &new_id) in new_ids.iter().enumerate() {
+            if let Some(new_id) = new_id {
+                suffix_links[new_id] = self.suffix_link(node).and_then(|link| new_ids[link]);
+            }
+        }
+
+        self.nodes = nodes;
+        self.suffix_links = suffix_links;
+        self.max_depth = Some(max_depth);
+    }
+}
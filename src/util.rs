@@ -0,0 +1,20 @@
+//! Small helpers shared by the examples, tests and benchmarks.
+
+/// Returns the data without its last byte if that byte is `byte`, and the data
+/// unchanged otherwise. Meant for lines read with `read_until`, where the last
+/// line may lack the delimiter.
+///
+/// #Examples
+/// ```
+/// use suffix_tree::util::strip_trailing;
+///
+/// assert_eq!(strip_trailing(b"GATTACA\n", b'\n'), b"GATTACA");
+/// assert_eq!(strip_trailing(b"GATTACA", b'\n'), b"GATTACA");
+/// assert_eq!(strip_trailing(b"", b'\n'), b"");
+/// ```
+pub fn strip_trailing(data: &[u8], byte: u8) -> &[u8] {
+    match data.split_last() {
+        Some((&last, rest)) if last == byte => rest,
+        _ => data,
+    }
+}
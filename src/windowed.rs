@@ -0,0 +1,130 @@
+use alloc::vec::Vec;
+use alphabet::{self, Alphabet, AlphabetError};
+use {OwnedSuffixTree, SuffixTree};
+
+/// A suffix tree over the last `window_size` bytes of a stream.
+///
+/// Suffix trees do not support removing a prefix, so the window is not kept
+/// in one tree. Instead a tree is built over a snapshot of the window, and
+/// the bytes pushed since then are kept in a buffer. Queries combine the
+/// occurences in the snapshot that still lie within the window with a direct
+/// scan of the part of the window the snapshot does not cover. The results
+/// are therefore always exact, nothing in the index is stale.
+///
+/// Once half a window has been pushed since the last snapshot, the tree is
+/// rebuilt over the current window. This bounds the scan done by queries to
+/// half a window and makes pushing a byte take amortized constant time.
+///
+/// #Examples
+///
+/// ```
+/// use suffix_tree::WindowedSuffixTree;
+///
+/// let mut window = WindowedSuffixTree::new(8, None);
+/// for &byte in b"abcabcxyzabc" {
+///     window.push(byte);
+/// }
+///
+/// // Only "xyzabc" and the preceding "bc" are still in the window.
+/// assert_eq!(window.find(b"abc"), vec![(9, 12)]);
+/// assert_eq!(window.find(b"bc"), vec![(4, 6), (10, 12)]);
+/// ```
+pub struct WindowedSuffixTree<'b> {
+    window_size: usize,
+    alphabet: Alphabet<'b>,
+    // The bytes from `buffer_start` up to the end of the stream. This always
+    // covers the window, and is trimmed to it whenever the tree is rebuilt.
+    buffer: Vec<u8>,
+    buffer_start: usize,
+    // The tree over the snapshot starting at `buffer_start` and ending at
+    // `snapshot_end`, if the snapshot is not empty.
+    tree: Option<OwnedSuffixTree<'b>>,
+    snapshot_end: usize,
+}
+
+impl<'b> WindowedSuffixTree<'b> {
+    /// Creates an empty index over windows of `window_size` bytes. Pushed
+    /// bytes have to be part of the alphabet.
+    pub fn new(window_size: usize, alphabet: Option<Alphabet<'b>>) -> WindowedSuffixTree<'b> {
+        assert!(window_size > 0, "Window size must be positive.");
+
+        WindowedSuffixTree {
+            window_size,
+            alphabet: alphabet.unwrap_or_else(alphabet::default_alphabet),
+            buffer: Vec::new(),
+            buffer_start: 0,
+            tree: None,
+            snapshot_end: 0,
+        }
+    }
+
+    /// Returns the number of bytes pushed so far.
+    pub fn len(&self) -> usize {
+        self.buffer_start + self.buffer.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns the position in the stream of the oldest byte in the window.
+    pub fn window_start(&self) -> usize {
+        self.len().saturating_sub(self.window_size)
+    }
+
+    /// Appends a byte to the stream, dropping the oldest byte from the window
+    /// once it is full.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the byte is not part of the alphabet.
+    pub fn push(&mut self, byte: u8) {
+        if !self.alphabet.contains(byte) {
+            panic!("{}", AlphabetError::UnknownSymbol(byte));
+        }
+        self.buffer.push(byte);
+
+        if self.len() - self.snapshot_end >= (self.window_size / 2).max(1) {
+            self.rebuild();
+        }
+    }
+
+    fn rebuild(&mut self) {
+        let window_start = self.window_start();
+        self.buffer.drain(..window_start - self.buffer_start);
+        self.buffer_start = window_start;
+
+        let tree = SuffixTree::from_sequences_owned(vec![self.buffer.clone()], Some(self.alphabet.clone()));
+        self.tree = Some(tree);
+        self.snapshot_end = self.len();
+    }
+
+    /// Returns the start and end positions in the stream of all occurences of
+    /// the given pattern within the window, in ascending order.
+    pub fn find(&self, pattern: &[u8]) -> Vec<(usize, usize)> {
+        let window_start = self.window_start();
+        if pattern.is_empty() || pattern.len() > self.len() - window_start {
+            return Vec::new();
+        }
+
+        let mut occurences: Vec<(usize, usize)> = match self.tree {
+            Some(ref tree) => tree.tree().find(pattern)
+                .map(|(_, start, end)| (self.buffer_start + start, self.buffer_start + end))
+                .filter(|&(start, _)| start >= window_start)
+                .collect(),
+            None => Vec::new(),
+        };
+        occurences.sort_unstable();
+
+        // Occurences ending after the snapshot are not in the tree.
+        let first_unindexed = (self.snapshot_end + 1).saturating_sub(pattern.len()).max(window_start);
+        for start in first_unindexed..=(self.len() - pattern.len()) {
+            let offset = start - self.buffer_start;
+            if &self.buffer[offset..offset + pattern.len()] == pattern {
+                occurences.push((start, start + pattern.len()));
+            }
+        }
+
+        occurences
+    }
+}
@@ -1,10 +1,13 @@
 #[macro_use] extern crate indoc;
 extern crate suffix_tree;
 
-use suffix_tree::{longest_common_subsequence, SuffixTree};
+use suffix_tree::{longest_common_subsequence, ChildStorage, PrettyPrintOptions, PreparedPatterns, QueryCache};
+use suffix_tree::{SuffixTree, SuffixTreeBuilder, TerminalPolicy, WindowedSuffixTree};
 use suffix_tree::alphabet::Alphabet;
+use suffix_tree::util::strip_trailing;
 use std::fs::File;
 use std::io::{BufRead, BufReader};
+use std::ops::ControlFlow;
 
 #[test]
 fn build_suffix_tree() {
@@ -14,16 +17,16 @@ fn build_suffix_tree() {
 #[test]
 fn pretty_print() {
     let expected = indoc!(
-        "┳t┳est$0
-         ┃ ┣$0
-         ┃ ┗$1
-         ┣$0
-         ┣rest$1
+        "┳$0
+         ┣$1
          ┣est┳$0
          ┃   ┗$1
+         ┣rest$1
          ┣st┳$0
          ┃  ┗$1
-         ┗$1"
+         ┗t┳$0
+           ┣$1
+           ┗est$0"
     );
 
     let tree = SuffixTree::from_sequences(&[b"test", b"rest"], None);
@@ -59,7 +62,7 @@ fn lcs_codon_sequences() {
     let mut owned_sequences: Vec<Vec<u8>> = Vec::new();
     loop {
         let mut sequence = Vec::new();
-        if reader.read_until('\n' as u8, &mut sequence).unwrap() == 0 {
+        if reader.read_until(b'\n', &mut sequence).unwrap() == 0 {
             break;
         }
 
@@ -67,10 +70,731 @@ fn lcs_codon_sequences() {
     }
 
     let alphabet = Alphabet::new(b"ATGC");
-    let sequences: Vec<&[u8]> = owned_sequences.iter().map(|s| {
-        let slice = s.as_slice();
-        &slice[..slice.len() - 1]
-    }).collect();
+    let sequences: Vec<&[u8]> = owned_sequences.iter().map(|s| strip_trailing(s, b'\n')).collect();
 
     assert_eq!(longest_common_subsequence(&sequences, Some(alphabet)).unwrap(), expected);
 }
+
+#[test]
+fn frequent_substrings() {
+    let sequences: &[&[u8]] = &[b"testing", b"resting", b"fasting"];
+    let tree = SuffixTree::from_sequences(sequences, None);
+
+    let mut substrings: Vec<&[u8]> = tree.frequent_substrings(4, 2).into_iter()
+        .map(|(seq_id, start, end)| &tree.sequence_by_id(seq_id)[start..end])
+        .collect();
+    substrings.sort();
+
+    let expected: Vec<&[u8]> = vec![b"esting", b"sting", b"ting"];
+    assert_eq!(substrings, expected);
+}
+
+#[test]
+fn pretty_print_with_node_ids() {
+    let expected = indoc!(
+        "┳$0 (7, depth 1)
+         ┣ab (3, depth 2) -> 5┳$0 (4, depth 3)
+         ┃                    ┗ab$0 (1, depth 5)
+         ┗b (5, depth 1)┳$0 (6, depth 2)
+                        ┗ab$0 (2, depth 4)"
+    );
+
+    let tree = SuffixTree::from_sequence(b"abab", None);
+    let options = PrettyPrintOptions {
+        show_node_ids: true,
+        show_suffix_links: true,
+        ..Default::default()
+    };
+
+    assert_eq!(tree.pretty_print_with(options), expected);
+}
+
+#[test]
+fn walk_cursor() {
+    let tree = SuffixTree::from_sequence(b"abcabd", None);
+    let mut cursor = tree.walk(b"").unwrap();
+
+    for &byte in b"ab" {
+        assert!(cursor.step(byte));
+    }
+
+    let mut occurences: Vec<_> = cursor.occurences().collect();
+    occurences.sort();
+    assert_eq!(occurences, vec![(0, 0, 2), (0, 3, 5)]);
+
+    assert!(cursor.step(b'd'));
+    assert!(!cursor.step(b'a'));
+    assert_eq!(cursor.occurences().collect::<Vec<_>>(), vec![(0, 3, 6)]);
+}
+
+#[test]
+fn suffixes_matching() {
+    let tree = SuffixTree::from_sequence(b"abab", None);
+
+    assert_eq!(tree.suffixes_matching(b"ab"), vec![(0, 2)]);
+    assert_eq!(tree.suffixes_matching(b"bab"), vec![(0, 1)]);
+    assert_eq!(tree.suffixes_matching(b"a"), vec![]);
+    assert_eq!(tree.suffixes_matching(b""), vec![(0, 4)]);
+
+    let tree = SuffixTree::from_sequences(&[b"abab", b"bb", b"ab"], None);
+    assert_eq!(tree.suffixes_matching(b"b"), vec![(0, 3), (1, 1), (2, 1)]);
+    assert_eq!(tree.suffixes_matching(b"ab"), vec![(0, 2), (2, 0)]);
+}
+
+#[test]
+fn find_str_respects_char_boundaries() {
+    let sequences: &[&[u8]] = &["aé".as_bytes(), "ĩ".as_bytes()];
+    let alphabet = Alphabet::new(&[b'a', 0xC3, 0xC4, 0xA9]);
+    let tree = SuffixTree::from_sequences(sequences, Some(alphabet));
+
+    // 'é' is encoded as C3 A9 and 'ĩ' as C4 A9, so both share their last byte.
+    assert_eq!(tree.find(&[0xA9]).count(), 2);
+    assert_eq!(tree.find_str("é").collect::<Vec<_>>(), vec![(0, 1, 3)]);
+}
+
+#[test]
+fn substrings_up_to() {
+    let tree = SuffixTree::from_sequences(&[b"abc", b"bcd"], None);
+    let substrings = tree.substrings_up_to(2);
+
+    let mut counts: Vec<(&[u8], usize)> = substrings.iter()
+        .map(|(substring, &count)| (substring.as_slice(), count))
+        .collect();
+    counts.sort();
+
+    let expected: Vec<(&[u8], usize)> = vec![
+        (b"a", 1), (b"ab", 1), (b"b", 2), (b"bc", 2), (b"c", 2), (b"cd", 1), (b"d", 1),
+    ];
+    assert_eq!(counts, expected);
+}
+
+#[test]
+fn bwt_generalized() {
+    let alphabet = Alphabet::new(b"ACGT");
+    let tree = SuffixTree::from_sequences(&[b"ACGTAC", b"GATTACA"], Some(alphabet));
+
+    assert_eq!(tree.bwt(0), b"CT$AACG");
+    assert_eq!(tree.bwt(1), b"ACTGA$TA");
+}
+
+#[test]
+fn find_within_edit_distance() {
+    let tree = SuffixTree::from_sequences(&[b"banana", b"bandana"], None);
+
+    let mut exact = tree.find_within_edit_distance(b"ana", 0);
+    exact.sort();
+    let mut expected: Vec<_> = tree.find(b"ana").collect();
+    expected.sort();
+    assert_eq!(exact, expected);
+
+    let mut fuzzy = tree.find_within_edit_distance(b"bnda", 1);
+    fuzzy.sort();
+    assert_eq!(fuzzy, vec![(1, 0, 5), (1, 1, 5), (1, 2, 5)]);
+}
+
+#[test]
+fn find_within_edit_distance_never_reports_empty_matches() {
+    let tree = SuffixTree::from_sequence(b"xyz", None);
+
+    // Deleting the whole pattern is within the limit, but only the non-empty
+    // matches at every start before the end of the sequence are reported.
+    let mut occurences = tree.find_within_edit_distance(b"ab", 2);
+    occurences.sort();
+    assert_eq!(occurences, vec![(0, 0, 1), (0, 1, 2), (0, 2, 3)]);
+
+    let tree = SuffixTree::from_sequence(b"xa", None);
+    assert_eq!(tree.find_within_edit_distance(b"ab", 1), vec![(0, 1, 2)]);
+    assert_eq!(tree.find_within_edit_distance(b"", 1), vec![]);
+}
+
+#[test]
+fn to_json_escapes_labels() {
+    let alphabet = Alphabet::new(&[b'"', 0xFE]);
+    let tree = SuffixTree::from_sequence(&[b'"', 0xFE], Some(alphabet));
+
+    assert!(tree.to_json().contains(r#"{"edge":"\"\\xfe","seq_id":0,"start":0}"#));
+}
+
+#[test]
+fn resume_matches_direct_build() {
+    let tree = SuffixTree::from_sequences(&[b"test", b"rest"], None);
+    let mut builder = SuffixTree::from_sequence(b"test", None).resume();
+    builder.add_sequence(b"rest");
+
+    assert_eq!(builder.build().pretty_print(), tree.pretty_print());
+}
+
+#[test]
+fn find_with_unknown_symbol() {
+    let tree = SuffixTree::from_sequence(b"test", None);
+
+    assert!(!tree.contains(b"t!"));
+    assert_eq!(tree.find(b"!").count(), 0);
+}
+
+#[test]
+fn occurences_len() {
+    let tree = SuffixTree::from_sequences(&[b"banana", b"ananas"], None);
+
+    let mut occurences = tree.find(b"ana");
+    assert_eq!(occurences.len(), 4);
+    occurences.next();
+    assert_eq!(occurences.len(), 3);
+    assert_eq!(occurences.count(), 3);
+
+    assert_eq!(tree.find(b"x").len(), 0);
+    assert_eq!(tree.longest_common_subsequence().len(), 2);
+}
+
+#[test]
+fn pretty_print_identical_sequences() {
+    let expected = indoc!(
+        "┳$0
+         ┣$1
+         ┣$2
+         ┗a┳$0
+           ┣$1
+           ┗$2"
+    );
+
+    let tree = SuffixTree::from_sequences(&[b"a", b"a", b"a"], None);
+
+    assert_eq!(tree.pretty_print(), expected);
+}
+
+#[test]
+fn sparse_child_storage() {
+    let mut builder = SuffixTreeBuilder::new(None).with_child_storage(ChildStorage::Sparse);
+    builder.add_sequence(b"mississippi");
+    builder.add_sequence(b"missouri");
+    let sparse = builder.build();
+
+    let dense = SuffixTree::from_sequences(&[b"mississippi", b"missouri"], None);
+
+    assert_eq!(sparse.pretty_print(), dense.pretty_print());
+    assert_eq!(sparse.find(b"ssi").count(), 2);
+
+    let mut builder = dense.resume();
+    builder.reset();
+    let mut builder = builder.with_child_storage(ChildStorage::Sparse);
+    builder.add_sequence(b"mississippi");
+    builder.add_sequence(b"missouri");
+    let switched = builder.build();
+
+    assert_eq!(switched.check_invariants(), Ok(()));
+    assert_eq!(switched.pretty_print(), sparse.pretty_print());
+}
+
+#[test]
+fn check_invariants() {
+    let sequences: [&[u8]; 5] = [b"mississippi", b"missouri", b"aaaaaaa", b"abcabxabcd", b"aaaaaaa"];
+    let tree = SuffixTree::from_sequences(&sequences, None);
+    assert_eq!(tree.check_invariants(), Ok(()));
+
+    let mut builder = SuffixTreeBuilder::new(None).with_child_storage(ChildStorage::Sparse);
+    builder.add_sequence(b"banana");
+    builder.add_concatenated(b"ananas#bandana", b"#");
+    assert_eq!(builder.build().check_invariants(), Ok(()));
+
+    let mut builder = SuffixTree::from_sequence(b"xabxac", None).resume();
+    builder.add_sequence(b"abxa");
+    assert_eq!(builder.tree().check_invariants(), Ok(()));
+    let resumed = builder.build();
+    assert_eq!(resumed.check_invariants(), Ok(()));
+
+    let merged = resumed.merge(SuffixTree::from_sequence(b"cacao", None));
+    assert_eq!(merged.check_invariants(), Ok(()));
+}
+
+#[test]
+fn many_sequences() {
+    // Prefix every sequence with its id written as two letters.
+    let owned: Vec<Vec<u8>> = (0..300).map(|i: usize| {
+        let mut sequence = vec![b'A' + (i / 26) as u8, b'A' + (i % 26) as u8];
+        sequence.extend_from_slice(b"common");
+        sequence
+    }).collect();
+    let sequences: Vec<&[u8]> = owned.iter().map(|sequence| &sequence[..]).collect();
+    let tree = SuffixTree::from_sequences(&sequences, None);
+    assert_eq!(tree.check_invariants(), Ok(()));
+
+    for (seq_id, sequence) in sequences.iter().enumerate() {
+        let occurences: Vec<_> = tree.find(sequence).collect();
+        assert_eq!(occurences, vec![(seq_id, 0, sequence.len())]);
+    }
+
+    assert_eq!(tree.sequences_containing(b"common").len(), 300);
+    assert_eq!(tree.sequences_containing(b"BD"), vec![29]);
+
+    let (seq_id, start, end) = tree.longest_common_subsequence().next().unwrap();
+    assert_eq!(&tree.sequence_by_id(seq_id)[start..end], b"common");
+
+    let (seq_id, start, end) = tree.longest_common_substring_pair(17, 43).unwrap();
+    assert_eq!(&tree.sequence_by_id(seq_id)[start..end], b"Rcommon");
+}
+
+#[test]
+fn count_non_overlapping() {
+    let tree = SuffixTree::from_sequences(&[b"aaaa", b"abaabaaba", b"b"], None);
+
+    assert_eq!(tree.find(b"aa").count(), 5);
+    assert_eq!(tree.count_non_overlapping(b"aa"), 2 + 2);
+    assert_eq!(tree.count_non_overlapping(b"aba"), 3);
+    assert_eq!(tree.count_non_overlapping(b"abaa"), 1);
+    assert_eq!(tree.count_non_overlapping(b"b"), 4);
+    assert_eq!(tree.count_non_overlapping(b"c"), 0);
+}
+
+#[test]
+fn find_with_wildcards() {
+    let tree = SuffixTree::from_sequences(&[b"abcabd", b"xbcy"], None);
+
+    let mut occurences = tree.find_with_wildcards(b"?bc", b'?');
+    occurences.sort();
+    assert_eq!(occurences, vec![(0, 0, 3), (1, 0, 3)]);
+
+    let mut occurences = tree.find_with_wildcards(b"ab?", b'?');
+    occurences.sort();
+    assert_eq!(occurences, vec![(0, 0, 3), (0, 3, 6)]);
+
+    let mut occurences = tree.find_with_wildcards(b"b??", b'?');
+    occurences.sort();
+    assert_eq!(occurences, vec![(0, 1, 4), (1, 1, 4)]);
+
+    let mut without_wildcards = tree.find_with_wildcards(b"bc", b'?');
+    without_wildcards.sort();
+    let mut expected: Vec<_> = tree.find(b"bc").collect();
+    expected.sort();
+    assert_eq!(without_wildcards, expected);
+
+    assert!(tree.find_with_wildcards(b"????????", b'?').is_empty());
+}
+
+#[test]
+fn matches_end_before_terminal() {
+    let tree = SuffixTree::from_sequences(&[b"test", b"contest"], None);
+    let find = |pattern: &[u8]| {
+        let mut occurences: Vec<_> = tree.find(pattern).collect();
+        occurences.sort();
+        occurences
+    };
+
+    assert_eq!(find(b"test"), vec![(0, 0, 4), (1, 3, 7)]);
+    assert_eq!(find(b"contest"), vec![(1, 0, 7)]);
+    assert_eq!(find(b"st"), vec![(0, 2, 4), (1, 5, 7)]);
+    assert_eq!(find(b"t"), vec![(0, 0, 1), (0, 3, 4), (1, 3, 4), (1, 6, 7)]);
+    assert!(find(b"contestt").is_empty());
+
+    let mut cursor = tree.walk(b"contest").unwrap();
+    assert_eq!(cursor.depth(), 7);
+    assert!(!cursor.step(b't'));
+
+    for (seq_id, start, end) in tree.find(b"est") {
+        assert!(start < end && end <= tree.sequence_by_id(seq_id).len());
+    }
+
+    let cache = QueryCache::new(&tree, 4);
+    let mut cached: Vec<_> = cache.find(b"st").collect();
+    cached.sort();
+    assert_eq!(cached, find(b"st"));
+
+    assert_eq!(tree.find_in_range(b"st", 1, 0, 7), vec![(5, 7)]);
+    assert_eq!(tree.find_with_wildcards(b"?ontest", b'?'), vec![(1, 0, 7)]);
+}
+
+#[test]
+fn truncated_tree_has_fewer_nodes() {
+    let sequence = b"abaababaabaababaababaabaababaabaab";
+    let full = SuffixTree::from_sequence(sequence, None);
+
+    let mut builder = SuffixTreeBuilder::new(None).with_max_depth(4);
+    builder.add_sequence(sequence);
+    let truncated = builder.build();
+
+    assert_eq!(truncated.check_invariants(), Ok(()));
+    fn node_count(tree: &SuffixTree, node: usize) -> usize {
+        1 + tree.children(node).map(|child| node_count(tree, child)).sum::<usize>()
+    }
+    assert!(node_count(&truncated, 0) < node_count(&full, 0));
+    assert_eq!(truncated.find(b"abaa").count(), full.find(b"abaa").count());
+    assert_eq!(truncated.find(b"abaab").count(), 0);
+
+    let mut cursor = truncated.walk(b"aba").unwrap();
+    assert!(cursor.step(b'a'));
+    assert!(!cursor.step(b'b'));
+}
+
+#[test]
+fn suffixes_matching_in_truncated_tree() {
+    let mut builder = SuffixTreeBuilder::new(None).with_max_depth(3);
+    builder.add_sequence(b"abcabcabc");
+    builder.add_sequence(b"cabc");
+    let tree = builder.build();
+
+    assert_eq!(tree.check_invariants(), Ok(()));
+    assert_eq!(tree.suffixes_matching(b"abc"), vec![(0, 6), (1, 1)]);
+    assert_eq!(tree.suffixes_matching(b"bc"), vec![(0, 7), (1, 2)]);
+    assert_eq!(tree.suffixes_matching(b"cabc"), vec![]);
+    assert_eq!(tree.find(b"abc").count(), 4);
+}
+
+#[test]
+fn pretty_print_escapes_split_characters() {
+    let expected = indoc!(
+        "┳$0
+         ┣nä┳$0
+         ┃  ┗nä$0
+         ┣ä┳$0
+         ┃ ┗nä$0
+         ┗\\xa4┳$0
+              ┗nä$0"
+    );
+
+    let alphabet = Alphabet::new("näe".as_bytes());
+    let tree = SuffixTree::from_str("nänä", Some(alphabet));
+
+    assert_eq!(tree.pretty_print(), expected);
+}
+
+#[test]
+fn resume_after_shrink() {
+    let tree = SuffixTree::from_sequences(&[b"mississippi", b"missouri", b"sassafras"], None);
+
+    let mut shrunk = SuffixTree::from_sequence(b"mississippi", None);
+    shrunk.shrink();
+    let mut builder = shrunk.resume();
+    builder.add_sequence(b"missouri");
+    builder.add_sequence(b"sassafras");
+    let resumed = builder.build();
+
+    assert_eq!(resumed.check_invariants(), Ok(()));
+    assert_eq!(resumed.pretty_print(), tree.pretty_print());
+}
+
+#[test]
+#[should_panic(expected = "symbol 'N' at position 2 of sequence 1 is not part of the alphabet")]
+fn add_sequence_with_unknown_symbol() {
+    let mut builder = SuffixTreeBuilder::new(Some(Alphabet::new(b"ACGT")));
+    builder.add_sequence(b"GATTACA");
+    builder.add_sequence(b"GANTACA");
+}
+
+#[test]
+fn duplicate_sequences_with_distinct_terminals() {
+    let mut builder = SuffixTreeBuilder::new(None);
+    builder.add_sequence(b"test");
+    builder.add_sequence(b"test");
+    let tree = builder.build();
+
+    assert_eq!(tree.sequence_count(), 2);
+    assert_eq!(tree.find(b"est").count(), 2);
+}
+
+#[test]
+fn dedup_after_distinct_sequences() {
+    // The sequences added before the first lookup are still found, and the
+    // first of two identical ones wins.
+    let mut builder = SuffixTreeBuilder::new(None);
+    builder.add_sequence(b"test");
+    builder.add_sequence(b"rest");
+    builder.add_sequence(b"test");
+    assert_eq!(builder.add_sequence_dedup(b"test"), 0);
+    assert_eq!(builder.add_sequence_dedup(b"nest"), 3);
+    assert_eq!(builder.add_sequence_dedup(b"nest"), 3);
+
+    let mut builder = builder.build().resume().with_terminal_policy(TerminalPolicy::Shared);
+    assert_eq!(builder.try_add_sequence(b"rest"), Ok(1));
+    assert_eq!(builder.build().sequence_count(), 4);
+}
+
+#[test]
+#[should_panic(expected = "sequence is identical to sequence 0")]
+fn add_rejected_duplicate_sequence() {
+    let mut builder = SuffixTreeBuilder::new(None).with_terminal_policy(TerminalPolicy::Error);
+    builder.add_sequence(b"test");
+    builder.add_sequence(b"rest");
+    builder.add_sequence(b"test");
+}
+
+#[test]
+fn leaf_suffixes() {
+    fn collect_suffixes<'a>(tree: &SuffixTree<'a, '_>, node: usize, suffixes: &mut Vec<(usize, &'a [u8])>) {
+        if tree.children(node).next().is_none() {
+            suffixes.push(tree.leaf_suffix(node));
+        }
+
+        for child in tree.children(node) {
+            collect_suffixes(tree, child, suffixes);
+        }
+    }
+
+    let sequences: &[&[u8]] = &[b"mississippi", b"missouri"];
+    let tree = SuffixTree::from_sequences(sequences, None);
+
+    let mut suffixes = Vec::new();
+    collect_suffixes(&tree, 0, &mut suffixes);
+    suffixes.sort();
+
+    let mut expected: Vec<(usize, &[u8])> = sequences.iter().enumerate().flat_map(|(seq_id, sequence)| {
+        (0..=sequence.len()).map(move |start| (seq_id, &sequence[start..]))
+    }).collect();
+    expected.sort();
+
+    assert_eq!(suffixes, expected);
+}
+
+#[test]
+fn debug_format() {
+    let builder = SuffixTree::builder_from_sequence(b"abab", None);
+    assert_eq!(
+        format!("{:?}", builder),
+        "SuffixTreeBuilder { \
+            tree: SuffixTree { node_count: 8, sequence_count: 1, alphabet_size: 52, max_depth: None }, \
+            active_node: 0, active_edge: None, position: 5, remaining: 0 }"
+    );
+}
+
+#[test]
+fn weighted_common_substring() {
+    let tree = SuffixTree::from_sequences(&[b"abcabc", b"xyz", b"xyq"], None);
+
+    // "abc" repeats within a single sequence only, so it is not common.
+    let (seq_id, start, end) = tree.weighted_common_substring(&[10.0, 1.0, 1.0]).unwrap();
+    assert_eq!(&tree.sequence_by_id(seq_id)[start..end], b"xy");
+
+    assert_eq!(SuffixTree::from_sequence(b"abab", None).weighted_common_substring(&[1.0]), None);
+}
+
+#[test]
+fn labels_of_shared_sequences() {
+    let mut builder = SuffixTreeBuilder::new(None).with_terminal_policy(TerminalPolicy::Shared).labeled();
+    assert_eq!(builder.add_sequence_labeled(b"test", "first"), 0);
+    assert_eq!(builder.add_sequence_labeled(b"rest", "second"), 1);
+    assert_eq!(builder.add_sequence_labeled(b"test", "third"), 0);
+
+    let (tree, labels) = builder.build().into_parts();
+    assert_eq!(tree.sequence_count(), 2);
+    assert_eq!(labels, vec!["first", "second"]);
+}
+
+#[test]
+fn from_reader_rejects_byte_255() {
+    let error = SuffixTree::from_reader(&[b'a', 255, b'b'][..]).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn empty_sequences() {
+    let expected = indoc!(
+        "┳$0
+         ┣$1
+         ┣$2
+         ┣$3
+         ┣ab┳$1
+         ┃  ┗$3
+         ┗b┳$1
+           ┣$3
+           ┗ab$3"
+    );
+
+    let mut builder = SuffixTreeBuilder::new(None);
+    for sequence in &[&b""[..], b"ab", b"", b"bab"] {
+        builder.add_sequence(sequence);
+    }
+    let tree = builder.build();
+
+    assert_eq!(tree.check_invariants(), Ok(()));
+    assert_eq!(tree.pretty_print(), expected);
+    assert_eq!(tree.find(b"ab").count(), 2);
+    assert_eq!(tree.sequences_containing(b""), vec![0, 1, 2, 3]);
+    assert_eq!(tree.leaves_by_sequence()[0], vec![0]);
+
+    // The longest common substring is the empty one, which has no occurences.
+    assert_eq!(tree.longest_common_subsequence().count(), 0);
+    assert_eq!(tree.all_longest_common_substrings(), vec![]);
+    assert_eq!(longest_common_subsequence(&[b"ab", b""], None), None);
+
+    let mut builder = tree.resume();
+    builder.add_sequence(b"");
+    builder.add_sequence(b"ba");
+    let tree = builder.build();
+    assert_eq!(tree.check_invariants(), Ok(()));
+    assert_eq!(tree.find(b"ba").count(), 2);
+}
+
+#[test]
+fn occurence_positions_in_deep_trees() {
+    // A run of a single byte nests an internal node for every length, and
+    // the repeats of a Fibonacci word nest almost as deeply.
+    let run = vec![b'a'; 500];
+    let mut fibonacci = (b"a".to_vec(), b"ab".to_vec());
+    while fibonacci.1.len() < 500 {
+        let next = [&fibonacci.1[..], &fibonacci.0[..]].concat();
+        fibonacci = (fibonacci.1, next);
+    }
+    let fibonacci = fibonacci.1;
+
+    let sequences: &[&[u8]] = &[&run, &fibonacci];
+    let tree = SuffixTree::from_sequences(sequences, None);
+    assert_eq!(tree.check_invariants(), Ok(()));
+
+    let find_naive = |pattern: &[u8]| -> Vec<(usize, usize, usize)> {
+        sequences.iter().enumerate().flat_map(|(seq_id, sequence)| {
+            (0..=sequence.len() - pattern.len())
+                .filter(move |&start| &sequence[start..start + pattern.len()] == pattern)
+                .map(move |start| (seq_id, start, start + pattern.len()))
+        }).collect()
+    };
+
+    let patterns = [1, 2, 5, 100, 250, 499, 500].iter().map(|&length| &run[..length])
+        .chain([1, 10, 100, 300].iter().map(|&length| &fibonacci[fibonacci.len() - length..]));
+    for pattern in patterns {
+        let mut occurences: Vec<_> = tree.find(pattern).collect();
+        occurences.sort();
+        assert_eq!(occurences, find_naive(pattern));
+
+        assert_eq!(tree.find_first(pattern), tree.find(pattern).next());
+        assert_eq!(tree.find_paged(pattern, 0, usize::MAX), tree.find(pattern).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn root_branches_with_both_storages() {
+    let alphabet = b"ACGTN";
+    for &storage in &[ChildStorage::Dense, ChildStorage::Sparse] {
+        let mut builder = SuffixTreeBuilder::new(Some(Alphabet::new(alphabet)))
+            .with_child_storage(storage);
+        builder.add_sequence(b"GATTACA");
+        builder.add_sequence(b"");
+        let tree = builder.build();
+
+        assert_eq!(tree.root_branches(), b"ACGT");
+    }
+
+    assert_eq!(SuffixTree::from_sequence(b"", None).root_branches(), vec![]);
+}
+
+#[test]
+fn long_run_of_one_symbol() {
+    // Every suffix of the run ends at its own internal node, so the tree is
+    // about as deep as the run is long.
+    let run = vec![b'a'; 1_000_000];
+    let sequences: &[&[u8]] = &[&run, b"aaa"];
+    let tree = SuffixTree::from_sequences(sequences, Some(Alphabet::new(b"a")));
+
+    assert_eq!(tree.find(b"aaa").count(), run.len() - 2 + 1);
+    assert_eq!(tree.find(&run[1..]).count(), 2);
+
+    let occurences: Vec<_> = tree.longest_common_subsequence().collect();
+    assert_eq!(occurences.len(), run.len() - 2 + 1);
+    assert!(occurences.iter().all(|&(_, start, end)| end - start == 3));
+    assert_eq!(tree.all_longest_common_substrings().len(), 1);
+
+    let (_, start, end) = tree.weighted_common_substring(&[1.0, 1.0]).unwrap();
+    assert_eq!(end - start, 3);
+
+    let mut count = 0;
+    let _ = tree.for_each_occurrence(&run[2..], |_, _, _| {
+        count += 1;
+        ControlFlow::<()>::Continue(())
+    });
+    assert_eq!(count, 3);
+
+    assert_eq!(tree.check_invariants(), Ok(()));
+
+    let same_tree = SuffixTree::from_sequences(sequences, Some(Alphabet::new(b"a")));
+    assert!(tree == same_tree);
+
+    let (start, end) = tree.longest_unique_substring(0).unwrap();
+    assert_eq!(end - start, run.len() - 1);
+
+    assert_eq!(tree.top_k_frequent(run.len() - 10, 1), vec![(run[10..].to_vec(), 11)]);
+
+    // The internal nodes are the runs of every length up to one less than
+    // the whole run.
+    assert_eq!(tree.frequent_substrings(4, 1).len(), run.len() - 4);
+
+    // Every line is indented by the depth of its leaf, so the printed tree
+    // grows quadratically and is only printed for a shorter run, which is
+    // still far deeper than a recursive traversal could go.
+    let short_run = &run[..10_000];
+    let tree = SuffixTree::from_sequence(short_run, Some(Alphabet::new(b"a")));
+    assert_eq!(tree.pretty_print().lines().count(), short_run.len() + 1);
+}
+
+#[test]
+fn prepared_patterns_match_find() {
+    let alphabet = Alphabet::new(b"ACGT");
+    let patterns: &[&[u8]] = &[b"A", b"CA", b"TTA", b"GG", b""];
+    let prepared = PreparedPatterns::new(patterns, &alphabet).unwrap();
+
+    let sequences: &[&[u8]] = &[b"GATTACA", b"CATTAG"];
+    for &storage in &[ChildStorage::Dense, ChildStorage::Sparse] {
+        let mut builder = SuffixTreeBuilder::new(Some(alphabet.clone())).with_child_storage(storage);
+        for sequence in sequences {
+            builder.add_sequence(sequence);
+        }
+        let tree = builder.build();
+
+        let expected: Vec<Vec<_>> = patterns.iter().map(|pattern| tree.find(pattern).collect()).collect();
+        assert_eq!(prepared.matches_in(&tree), expected);
+    }
+
+    assert!(PreparedPatterns::new(&[b"GANT"], &alphabet).is_err());
+}
+
+#[test]
+#[should_panic(expected = "different rank for symbol 'A'")]
+fn prepared_patterns_with_other_alphabet() {
+    let prepared = PreparedPatterns::new(&[b"CA"], &Alphabet::new(b"ACGT")).unwrap();
+    let tree = SuffixTree::from_sequence(b"GATTACA", Some(Alphabet::new(b"TGCA")));
+    prepared.matches_in(&tree);
+}
+
+#[test]
+#[should_panic(expected = "symbol 'N' is not part of the alphabet")]
+fn windowed_push_with_unknown_symbol() {
+    let mut window = WindowedSuffixTree::new(8, Some(Alphabet::new(b"ACGT")));
+    window.push(b'G');
+    // The window is only rebuilt after four bytes, the symbol has to be
+    // rejected right away.
+    window.push(b'N');
+}
+
+#[test]
+fn find_detailed_in_circular_tree() {
+    let owned = SuffixTree::from_circular(b"TACGGA", None);
+    let tree = owned.tree();
+
+    let mut occurences: Vec<_> = tree.find_detailed(b"A").collect();
+    occurences.sort();
+    assert_eq!(occurences, vec![(0, 1, 2, 5), (0, 5, 6, 1), (0, 7, 8, 5), (0, 11, 12, 1)]);
+
+    let occurences: Vec<_> = tree.find_detailed(b"GATA").collect();
+    assert_eq!(occurences, vec![(0, 4, 8, 2)]);
+}
+
+#[test]
+#[should_panic(expected = "has to hold the circular sequence twice in a row")]
+fn add_undoubled_sequence_to_circular_tree() {
+    let mut builder = SuffixTreeBuilder::new(None).with_circular(true);
+    builder.add_sequence(b"abcdabce");
+}
+
+#[test]
+#[should_panic(expected = "cannot become circular or stop being circular")]
+fn make_tree_circular_after_adding_sequences() {
+    let mut builder = SuffixTreeBuilder::new(None);
+    builder.add_sequence(b"abab");
+    builder.with_circular(true);
+}
+
+#[test]
+#[should_panic(expected = "Circular and linear trees cannot be merged.")]
+fn merge_circular_with_linear_tree() {
+    let mut builder = SuffixTreeBuilder::new(None).with_circular(true);
+    builder.add_sequence(b"abcabc");
+    let circular = builder.build();
+
+    circular.merge(SuffixTree::from_sequence(b"abcabc", None));
+}
@@ -0,0 +1,503 @@
+extern crate proptest;
+extern crate suffix_tree;
+
+use proptest::collection::vec;
+use std::collections::BTreeMap;
+use proptest::prelude::*;
+use suffix_tree::{ChildStorage, SuffixTree, SuffixTreeBuilder, WindowedSuffixTree};
+
+/// Finds all occurences of `pattern` by comparing it against every window of
+/// every sequence.
+fn find_naive(sequences: &[Vec<u8>], pattern: &[u8]) -> Vec<(usize, usize, usize)> {
+    let mut occurences = Vec::new();
+    for (seq_id, sequence) in sequences.iter().enumerate() {
+        for (start, window) in sequence.windows(pattern.len()).enumerate() {
+            if window == pattern {
+                occurences.push((seq_id, start, start + pattern.len()));
+            }
+        }
+    }
+
+    occurences
+}
+
+/// Decodes an LZ78 parse back into the sequence it was computed from.
+fn lz78_decode(pairs: &[(usize, u8)]) -> Vec<u8> {
+    let mut phrases: Vec<Vec<u8>> = vec![Vec::new()];
+    for &(phrase, byte) in pairs {
+        let mut next_phrase = phrases[phrase].clone();
+        next_phrase.push(byte);
+        phrases.push(next_phrase);
+    }
+
+    phrases.concat()
+}
+
+/// Finds the maximal tandem arrays by checking every start position and period.
+fn tandem_repeats_naive(sequence: &[u8]) -> Vec<(usize, usize)> {
+    let is_primitive = |word: &[u8]| {
+        (1..word.len()).all(|period| !word.len().is_multiple_of(period) || word[period..] != word[..word.len() - period])
+    };
+
+    let mut tandem_repeats = Vec::new();
+    for start in 0..sequence.len() {
+        for period in 1..=(sequence.len() - start) / 2 {
+            let word = &sequence[start..start + period];
+            let is_repeated = sequence[start + period..start + 2 * period] == *word;
+            let is_preceded = start >= period && sequence[start - period..start] == *word;
+            if is_repeated && !is_preceded && is_primitive(word) {
+                tandem_repeats.push((start, period));
+            }
+        }
+    }
+
+    tandem_repeats
+}
+
+// A small alphabet makes repeats, and therefore the tricky parts of the
+// construction, much more likely. Empty sequences are included, as they only
+// consist of the terminal symbol.
+fn sequence() -> impl Strategy<Value = Vec<u8>> {
+    vec(prop_oneof![Just(b'a'), Just(b'b'), Just(b'c')], 0..40)
+}
+
+fn storage() -> impl Strategy<Value = ChildStorage> {
+    prop_oneof![Just(ChildStorage::Dense), Just(ChildStorage::Sparse)]
+}
+
+proptest! {
+    #[test]
+    fn find_matches_naive_search(
+        sequences in vec(sequence(), 1..5),
+        patterns in vec(vec(prop_oneof![Just(b'a'), Just(b'b'), Just(b'c')], 1..6), 1..10),
+        child_storage in storage()
+    ) {
+        let mut builder = SuffixTreeBuilder::new(None).with_child_storage(child_storage);
+        for sequence in &sequences {
+            builder.add_sequence(sequence);
+        }
+        let tree = builder.build();
+
+        prop_assert_eq!(tree.check_invariants(), Ok(()));
+
+        for pattern in &patterns {
+            let mut occurences: Vec<_> = tree.find(pattern).collect();
+            occurences.sort();
+            prop_assert_eq!(occurences, find_naive(&sequences, pattern));
+        }
+    }
+
+    #[test]
+    fn truncated_find_matches_naive_search(
+        sequences in vec(sequence(), 1..5),
+        patterns in vec(vec(prop_oneof![Just(b'a'), Just(b'b'), Just(b'c')], 1..8), 1..10),
+        max_depth in 1usize..6
+    ) {
+        let mut builder = SuffixTreeBuilder::new(None).with_max_depth(max_depth);
+        for sequence in &sequences {
+            builder.add_sequence(sequence);
+        }
+        let tree = builder.build();
+
+        prop_assert_eq!(tree.check_invariants(), Ok(()));
+
+        for pattern in &patterns {
+            let mut occurences: Vec<_> = tree.find(pattern).collect();
+            occurences.sort();
+            if pattern.len() <= max_depth {
+                prop_assert_eq!(occurences, find_naive(&sequences, pattern));
+            } else {
+                prop_assert!(occurences.is_empty());
+            }
+        }
+    }
+
+    #[test]
+    fn every_substring_is_contained(sequences in vec(sequence(), 1..4)) {
+        let mut builder = SuffixTreeBuilder::new(None);
+        for sequence in &sequences {
+            builder.add_sequence(sequence);
+        }
+        let tree = builder.build();
+
+        for sequence in &sequences {
+            for start in 0..sequence.len() {
+                for end in start + 1..=sequence.len() {
+                    prop_assert!(tree.contains(&sequence[start..end]));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn lz78_parse_decodes_to_sequence(sequence in sequence()) {
+        let tree = SuffixTree::from_sequence(&sequence, None);
+        prop_assert_eq!(lz78_decode(&tree.lz78_parse(0)), sequence);
+    }
+
+    #[test]
+    fn windowed_find_matches_naive_search(
+        stream in sequence(),
+        patterns in vec(vec(prop_oneof![Just(b'a'), Just(b'b'), Just(b'c')], 1..6), 1..5),
+        window_size in 1usize..12
+    ) {
+        let mut window = WindowedSuffixTree::new(window_size, None);
+        for length in 1..=stream.len() {
+            window.push(stream[length - 1]);
+
+            let window_start = length.saturating_sub(window_size);
+            let contents = vec![stream[window_start..length].to_vec()];
+            for pattern in &patterns {
+                let expected: Vec<(usize, usize)> = find_naive(&contents, pattern).into_iter()
+                    .map(|(_, start, end)| (window_start + start, window_start + end))
+                    .collect();
+                prop_assert_eq!(window.find(pattern), expected);
+            }
+        }
+    }
+
+    #[test]
+    fn scan_matches_naive_search(
+        patterns in vec(vec(prop_oneof![Just(b'a'), Just(b'b'), Just(b'c')], 1..6), 1..8),
+        text in sequence(),
+        shrink in any::<bool>()
+    ) {
+        let mut builder = SuffixTreeBuilder::new(None);
+        for pattern in &patterns {
+            builder.add_sequence(pattern);
+        }
+        let mut tree = builder.build();
+        if shrink {
+            tree.shrink();
+        }
+
+        let texts = vec![text.clone()];
+        let mut expected: Vec<(usize, usize, usize)> = patterns.iter().enumerate().flat_map(|(seq_id, pattern)| {
+            find_naive(&texts, pattern).into_iter().map(move |(_, start, end)| (seq_id, start, end))
+        }).collect();
+        expected.sort_by_key(|&(seq_id, _, end)| (end, seq_id));
+
+        prop_assert_eq!(tree.scan(&text), expected);
+    }
+
+    #[test]
+    fn leaves_by_sequence_are_suffix_arrays(sequences in vec(sequence(), 1..4)) {
+        let mut builder = SuffixTreeBuilder::new(None);
+        for sequence in &sequences {
+            builder.add_sequence(sequence);
+        }
+        let tree = builder.build();
+
+        for (sequence, leaves) in sequences.iter().zip(tree.leaves_by_sequence()) {
+            let mut suffix_array: Vec<usize> = (0..=sequence.len()).collect();
+            suffix_array.sort_by_key(|&start| &sequence[start..]);
+            prop_assert_eq!(leaves, suffix_array);
+        }
+    }
+
+    #[test]
+    fn tandem_repeats_match_naive_search(sequence in sequence()) {
+        let tree = SuffixTree::from_sequence(&sequence, None);
+        prop_assert_eq!(tree.tandem_repeats(0), tandem_repeats_naive(&sequence));
+    }
+
+    #[test]
+    fn builds_of_the_same_input_are_equal(sequences in vec(sequence(), 1..4)) {
+        let mut dense = SuffixTreeBuilder::new(None);
+        let mut sparse = SuffixTreeBuilder::new(None).with_child_storage(ChildStorage::Sparse);
+        for sequence in &sequences {
+            dense.add_sequence(sequence);
+            sparse.add_sequence(sequence);
+        }
+        let tree = dense.build();
+
+        let mut resumed = SuffixTree::from_sequence(&sequences[0], None).resume();
+        for sequence in &sequences[1..] {
+            resumed.add_sequence(sequence);
+        }
+
+        prop_assert!(sparse.build() == tree);
+        prop_assert!(resumed.build() == tree);
+    }
+
+    #[test]
+    fn find_first_is_first_of_find(
+        sequences in vec(sequence(), 1..4),
+        pattern in vec(prop_oneof![Just(b'a'), Just(b'b'), Just(b'c')], 0..5),
+        child_storage in storage()
+    ) {
+        let mut builder = SuffixTreeBuilder::new(None).with_child_storage(child_storage);
+        for sequence in &sequences {
+            builder.add_sequence(sequence);
+        }
+        let tree = builder.build();
+
+        prop_assert_eq!(tree.find_first(&pattern), tree.find(&pattern).next());
+    }
+
+    #[test]
+    fn following_symbols_match_naive_counts(
+        sequences in vec(sequence(), 1..4),
+        context in vec(prop_oneof![Just(b'a'), Just(b'b'), Just(b'c')], 0..4)
+    ) {
+        let mut builder = SuffixTreeBuilder::new(None);
+        for sequence in &sequences {
+            builder.add_sequence(sequence);
+        }
+        let tree = builder.build();
+
+        let mut expected = BTreeMap::new();
+        for sequence in &sequences {
+            for window in sequence.windows(context.len() + 1) {
+                if window[..context.len()] == context[..] {
+                    *expected.entry(window[context.len()]).or_insert(0) += 1;
+                }
+            }
+        }
+
+        prop_assert_eq!(tree.following_symbols(&context), expected.into_iter().collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn reversed_find_reports_original_positions(
+        sequence in sequence(),
+        pattern in vec(prop_oneof![Just(b'a'), Just(b'b'), Just(b'c')], 1..5)
+    ) {
+        let owned = SuffixTree::from_sequence_reversed(&sequence, None);
+        let tree = owned.tree();
+        let reversed_pattern: Vec<u8> = pattern.iter().rev().cloned().collect();
+        let sequences = vec![sequence];
+        let sequence = &sequences[0];
+        let expected = find_naive(&sequences, &pattern);
+        prop_assert_eq!(tree.sequence_by_id(0), &sequence[..]);
+
+        let mut occurences: Vec<_> = tree.find(&reversed_pattern).collect();
+        occurences.sort();
+        prop_assert_eq!(&occurences, &expected);
+
+        for found in tree.find_matches(&reversed_pattern) {
+            prop_assert_eq!(found.as_bytes(), &pattern[..]);
+            prop_assert_eq!(&tree.sequence_by_id(0)[found.range()], &pattern[..]);
+        }
+
+        let mut occurences = tree.find_within_edit_distance(&reversed_pattern, 0);
+        occurences.sort();
+        prop_assert_eq!(&occurences, &expected);
+
+        let mut occurences: Vec<_> = tree.find_str(std::str::from_utf8(&reversed_pattern).unwrap()).collect();
+        occurences.sort();
+        prop_assert_eq!(&occurences, &expected);
+
+        let (lo, hi) = (sequence.len() / 3, 2 * sequence.len() / 3 + 1);
+        let in_range: Vec<_> = expected.iter()
+            .filter(|&&(_, start, _)| lo <= start && start < hi)
+            .map(|&(_, start, end)| (start, end))
+            .collect();
+        prop_assert_eq!(tree.find_in_range(&reversed_pattern, 0, lo, hi), in_range);
+
+        // Suffixes of the reversed sequence are the prefixes of the original.
+        let prefixes = if sequence.starts_with(&pattern) { vec![(0, 0)] } else { vec![] };
+        prop_assert_eq!(tree.suffixes_matching(&reversed_pattern), prefixes);
+
+        if let Some((start, end)) = tree.longest_k_repeat(0, 2) {
+            let repeat = &sequence[start..end];
+            prop_assert!(find_naive(&sequences, repeat).len() >= 2);
+        }
+    }
+
+    #[test]
+    fn longest_match_has_maximal_length(
+        sequences in vec(sequence(), 1..4),
+        query in vec(prop_oneof![Just(b'a'), Just(b'b'), Just(b'c')], 0..12)
+    ) {
+        let mut builder = SuffixTreeBuilder::new(None);
+        for sequence in &sequences {
+            builder.add_sequence(sequence);
+        }
+        let tree = builder.build();
+
+        let longest = (1..=query.len()).rev().find(|&length| {
+            query.windows(length).any(|window| !find_naive(&sequences, window).is_empty())
+        });
+
+        match tree.longest_match(&query) {
+            Some((seq_id, start, end)) => {
+                let matched = &sequences[seq_id][start..end];
+                prop_assert_eq!(Some(matched.len()), longest);
+                prop_assert!(query.windows(matched.len()).any(|window| window == matched));
+            },
+            None => prop_assert_eq!(longest, None),
+        }
+    }
+
+    #[test]
+    fn common_substrings_by_length_are_common_and_sorted(sequences in vec(sequence(), 1..4)) {
+        let mut builder = SuffixTreeBuilder::new(None);
+        for sequence in &sequences {
+            builder.add_sequence(sequence);
+        }
+        let tree = builder.build();
+
+        let substrings: Vec<_> = tree.common_substrings_by_length().collect();
+        prop_assert!(substrings.windows(2).all(|pair| pair[0].0 >= pair[1].0));
+
+        for &(length, seq_id, start, end) in &substrings {
+            let substring = &sequences[seq_id][start..end];
+            prop_assert_eq!(substring.len(), length);
+            let is_common = sequences.iter().all(|sequence| {
+                sequence.windows(length).any(|window| window == substring)
+            });
+            prop_assert!(is_common);
+        }
+
+        let longest = tree.longest_common_subsequence().next().map_or(0, |(_, start, end)| end - start);
+        prop_assert_eq!(substrings.first().map_or(0, |&(length, ..)| length), longest);
+    }
+
+    #[test]
+    fn overlap_matches_naive(a in sequence(), b in sequence()) {
+        let mut builder = SuffixTreeBuilder::new(None);
+        builder.add_sequence(&a);
+        builder.add_sequence(&b);
+        let tree = builder.build();
+
+        let naive = |a: &[u8], b: &[u8]| {
+            (0..=a.len().min(b.len())).rev().find(|&length| a[a.len() - length..] == b[..length]).unwrap()
+        };
+
+        prop_assert_eq!(tree.overlap(0, 1), naive(&a, &b));
+        prop_assert_eq!(tree.overlap(1, 0), naive(&b, &a));
+        prop_assert_eq!(tree.overlap(0, 0), a.len());
+    }
+
+    #[test]
+    fn filter_sequences_equals_direct_build(
+        sequences in vec(sequence(), 1..5),
+        kept in vec(any::<bool>(), 5),
+        child_storage in storage()
+    ) {
+        let mut builder = SuffixTreeBuilder::new(None).with_child_storage(child_storage);
+        let mut kept_builder = SuffixTreeBuilder::new(None).with_child_storage(child_storage);
+        for (seq_id, sequence) in sequences.iter().enumerate() {
+            builder.add_sequence(sequence);
+            if kept[seq_id] {
+                kept_builder.add_sequence(sequence);
+            }
+        }
+
+        let filtered = builder.build().filter_sequences(|seq_id| kept[seq_id]);
+        prop_assert_eq!(filtered.check_invariants(), Ok(()));
+        prop_assert!(filtered == kept_builder.build());
+    }
+
+    #[test]
+    fn top_k_frequent_matches_naive_counts(
+        sequences in vec(sequence(), 1..4),
+        length in 1usize..5,
+        k in 0usize..6
+    ) {
+        let mut builder = SuffixTreeBuilder::new(None);
+        for sequence in &sequences {
+            builder.add_sequence(sequence);
+        }
+        let tree = builder.build();
+
+        let mut counts = BTreeMap::new();
+        for sequence in &sequences {
+            for window in sequence.windows(length) {
+                *counts.entry(window.to_vec()).or_insert(0) += 1;
+            }
+        }
+        let mut expected: Vec<(Vec<u8>, usize)> = counts.into_iter().collect();
+        expected.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+        expected.truncate(k);
+
+        prop_assert_eq!(tree.top_k_frequent(length, k), expected);
+    }
+
+    #[test]
+    fn find_paged_is_a_slice_of_find(
+        sequences in vec(sequence(), 1..4),
+        pattern in vec(prop_oneof![Just(b'a'), Just(b'b'), Just(b'c')], 0..3),
+        offset in 0usize..20,
+        limit in 0usize..10
+    ) {
+        let mut builder = SuffixTreeBuilder::new(None);
+        for sequence in &sequences {
+            builder.add_sequence(sequence);
+        }
+        let tree = builder.build();
+
+        let expected: Vec<_> = tree.find(&pattern).skip(offset).take(limit).collect();
+        prop_assert_eq!(tree.find_paged(&pattern, offset, limit), expected);
+    }
+
+    #[test]
+    fn metrics_count_nodes(sequences in vec(sequence(), 1..4)) {
+        let mut builder = SuffixTreeBuilder::new(None).with_metrics();
+        for sequence in &sequences {
+            builder.add_sequence(sequence);
+        }
+        let metrics = builder.metrics().unwrap();
+        let tree = builder.build();
+
+        let mut internal_nodes = 0;
+        let mut stack = vec![0];
+        while let Some(node) = stack.pop() {
+            if node != 0 && tree.children(node).next().is_some() {
+                internal_nodes += 1;
+            }
+            stack.extend(tree.children(node));
+        }
+
+        let symbols: usize = sequences.iter().map(|sequence| sequence.len() + 1).sum();
+        prop_assert_eq!(metrics.symbols_processed, symbols);
+        prop_assert_eq!(metrics.leaf_insertions, symbols);
+        prop_assert_eq!(metrics.edge_splits, internal_nodes);
+    }
+
+    #[test]
+    fn longest_unique_substring_matches_naive(sequences in vec(sequence(), 1..4)) {
+        let mut builder = SuffixTreeBuilder::new(None);
+        for sequence in &sequences {
+            builder.add_sequence(sequence);
+        }
+        let tree = builder.build();
+
+        let target = &sequences[0];
+        let longest = (1..target.len()).rev().find(|&length| {
+            target.windows(length).any(|window| {
+                let occurences = find_naive(&sequences, window);
+                occurences.len() >= 2 && occurences.iter().all(|&(seq_id, _, _)| seq_id == 0)
+            })
+        });
+
+        let found = tree.longest_unique_substring(0);
+        prop_assert_eq!(found.map(|(start, end)| end - start), longest);
+        if let Some((start, end)) = found {
+            let occurences = find_naive(&sequences, &target[start..end]);
+            prop_assert!(occurences.len() >= 2);
+            prop_assert!(occurences.iter().all(|&(seq_id, _, _)| seq_id == 0));
+        }
+    }
+
+    #[test]
+    fn find_circular_matches_naive_search(
+        sequence in sequence(),
+        pattern in vec(prop_oneof![Just(b'a'), Just(b'b'), Just(b'c')], 1..6)
+    ) {
+        let owned = SuffixTree::from_circular(&sequence, None);
+
+        let length = sequence.len();
+        let expected: Vec<_> = (0..length)
+            .filter(|&start| {
+                pattern.len() <= length
+                    && pattern.iter().enumerate().all(|(i, &byte)| sequence[(start + i) % length] == byte)
+            })
+            .map(|start| (0, start, start + pattern.len()))
+            .collect();
+
+        prop_assert_eq!(owned.tree().find_circular(&pattern), expected);
+        prop_assert_eq!(owned.into_sequences(), vec![sequence]);
+    }
+}